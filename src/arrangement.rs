@@ -0,0 +1,140 @@
+// 目標配置の探索
+// スライドの手順を直接探すのではなく，まず入力のタイルを並べ替えて全域木になる配置(目標配置)を探す
+// 目標配置が見つかれば，あとはスライドパズルとしてそこへ動かせばよい
+use crate::board::Board;
+use crate::dsu::UnionFind;
+use crate::evaluation::{count_dangling, dangling_around};
+use crate::SolverRng;
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+// 閉路1つあたりのコスト
+// つながる相手のいない道がなくても，閉路があれば全域木にならない
+const CYCLE_COST: i32 = 2;
+
+// 時刻を確認する間隔(反復の回数)
+const TIME_CHECK_INTERVAL: usize = 256;
+
+// 見つかった配置
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Arrangement {
+    pub board: Board,
+    pub empty: (usize, usize),
+    // つながる相手のいない道の数 + CYCLE_COST * 閉路の数．0なら全域木
+    pub cost: i32,
+}
+
+impl Arrangement {
+    pub fn is_spanning_tree(&self) -> bool {
+        self.cost == 0
+    }
+}
+
+// 配置のコスト
+// タイルの道の本数の合計が2(N^2-2)なら，つながる相手のいない道がなく閉路もないことと全域木であることは同じ
+pub fn arrangement_cost(board: &Board) -> i32 {
+    count_dangling(board).total() + CYCLE_COST * cycle_count(board)
+}
+
+// 盤面全体の独立な閉路の数
+// 焼きなましの毎回の反復で呼ぶので，連結成分の情報は作らず，すでにつながっている2枚を結ぶ辺だけを数える
+fn cycle_count(board: &Board) -> i32 {
+    let n = board.n;
+    let mut uf = UnionFind::new(n * n);
+    let mut cycles = 0;
+    for i in 0..n {
+        for j in 0..n {
+            for dir in 0..2 {
+                if board.is_connected(i, j, dir) {
+                    let (x, y) = board.neighbor(i, j, dir).unwrap();
+                    if !uf.unite(i * n + j, x * n + y) {
+                        cycles += 1;
+                    }
+                }
+            }
+        }
+    }
+    cycles
+}
+
+// タイルの入れ替えによる焼きなまし法
+// 空きマスをemptyに固定し，残りのマスのタイルを2枚ずつ入れ替えてコストを0にする
+pub struct ArrangementAnnealing {
+    // 目標配置での空きマスの位置
+    pub empty: (usize, usize),
+    // 温度の初期値と最終値
+    // N=10で0.5から0.01に下げると，ほとんどのケースで1秒以内に全域木が見つかった
+    pub start_temperature: f64,
+    pub end_temperature: f64,
+    // この時刻を過ぎたら打ち切り，それまでのベストを返す
+    pub deadline: Instant,
+    // 1回のsearchで使う時間．温度はこの時間で下げきる
+    // 締め切りまで使い切ると，見つけた配置へ動かす時間もやり直す時間も残らない
+    pub budget: Duration,
+    pub rng: SolverRng,
+}
+
+impl ArrangementAnnealing {
+    // boardのタイルを並べ替えた配置のうち，コストが最も小さかったものを返す
+    // 全域木が見つからないまま時間が来ても，コストが最も小さかったものを返す
+    pub fn search(&mut self, board: &Board) -> Arrangement {
+        let n = board.n;
+        let start = Instant::now();
+        let duration = self.deadline.saturating_duration_since(start).min(self.budget).as_secs_f64().max(1e-9);
+
+        // 入力のタイルを行優先で，空きマスを飛ばして詰めていく
+        let mut tiles = board.tiles[..n * n].iter().copied().filter(|&tile| tile != 0);
+        let mut current = Board { n, tiles: board.tiles };
+        let cells: Vec<(usize, usize)> = (0..n * n).map(|p| (p / n, p % n)).filter(|&p| p != self.empty).collect();
+        current.set(self.empty.0, self.empty.1, 0);
+        for &(i, j) in &cells {
+            current.set(i, j, tiles.next().unwrap());
+        }
+
+        let mut dangling = count_dangling(&current).total();
+        let mut cost = dangling + CYCLE_COST * cycle_count(&current);
+        let mut best = Arrangement { board: current, empty: self.empty, cost };
+        let mut temperature = self.start_temperature;
+        let mut iteration = 0;
+        while best.cost > 0 {
+            if iteration % TIME_CHECK_INTERVAL == 0 {
+                let progress = start.elapsed().as_secs_f64() / duration;
+                if progress >= 1.0 {
+                    break;
+                }
+                temperature = self.start_temperature * (self.end_temperature / self.start_temperature).powf(progress);
+            }
+            iteration += 1;
+
+            let a = cells[self.rng.gen_range(0, cells.len())];
+            let b = cells[self.rng.gen_range(0, cells.len())];
+            let (tile_a, tile_b) = (current.get(a.0, a.1), current.get(b.0, b.1));
+            if tile_a == tile_b {
+                continue;
+            }
+            // つながる相手のいない道はa, bの周りだけ数え直す
+            let before = dangling_around(&current, a, b).total();
+            current.set(a.0, a.1, tile_b);
+            current.set(b.0, b.1, tile_a);
+            let next_dangling = dangling - before + dangling_around(&current, a, b).total();
+            // 先に受理できるコストの上限を決めておき，それを超えるなら閉路を数えずに棄却する
+            let threshold = cost as f64 - temperature * self.rng.gen_range(0.0f64, 1.0).ln();
+            let next_cost = if next_dangling as f64 <= threshold {
+                next_dangling + CYCLE_COST * cycle_count(&current)
+            } else {
+                i32::MAX
+            };
+            if next_cost as f64 <= threshold {
+                dangling = next_dangling;
+                cost = next_cost;
+                if cost < best.cost {
+                    best = Arrangement { board: current, empty: self.empty, cost };
+                }
+            } else {
+                current.set(a.0, a.1, tile_a);
+                current.set(b.0, b.1, tile_b);
+            }
+        }
+        best
+    }
+}
@@ -0,0 +1,200 @@
+// 入力の盤面と目標配置の間の両方向探索
+// 入力の盤面から前向きに，目標配置から逆向きに1手ずつ幅優先で広げ，Zobrist hashが一致した盤面でつなぐ
+// スライドは逆向きにたどってもスライドなので，逆向きの探索も同じ操作で広げ，つなぐときに操作を逆にする
+// 片方向だと深さdでおよそ3^d個の盤面を見るところを，両方向なら2 * 3^(d/2)個程度で済む
+use crate::board::Board;
+use crate::tiles::{char_to_action, reverse_action, ACTION_CHARS};
+use crate::zobrist::{hash_board, slide_diff};
+use rustc_hash::FxHashMap;
+use std::fmt;
+use std::time::Instant;
+
+// 時刻を確認する間隔(広げた盤面の数)
+const TIME_CHECK_INTERVAL: usize = 1024;
+
+// 探索を打ち切った理由
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BidirectionalError {
+    // 覚えた盤面の数がnode_limitを超えた
+    NodeLimit,
+    // deadlineを過ぎた
+    TimeLimit,
+    // どちらかの探索で広げられる盤面がなくなった(目標配置にたどり着けない)
+    Unreachable,
+}
+
+impl fmt::Display for BidirectionalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BidirectionalError::NodeLimit => write!(f, "node limit exceeded"),
+            BidirectionalError::TimeLimit => write!(f, "time limit exceeded"),
+            BidirectionalError::Unreachable => write!(f, "the target is not reachable"),
+        }
+    }
+}
+
+impl std::error::Error for BidirectionalError {}
+
+// 両方向探索の設定
+pub struct BidirectionalSearch {
+    // 両方向で覚える盤面の数の合計の上限
+    pub node_limit: usize,
+    // この時刻を過ぎたら打ち切る
+    pub deadline: Instant,
+}
+
+// 探索中の盤面と空きマス，ハッシュ，直前の操作
+type Node = (Board, (usize, usize), u64, Option<usize>);
+
+// 片方向の探索の状態
+struct Side {
+    // 覚えた盤面ごとの，1手前の盤面のハッシュとそこからの操作．始点は操作をNoneにする
+    parent: FxHashMap<u64, (u64, Option<usize>)>,
+    // いまの深さの盤面
+    frontier: Vec<Node>,
+}
+
+impl Side {
+    fn new(board: &Board, empty: (usize, usize)) -> Side {
+        let hash = hash_board(board, empty);
+        let mut parent = FxHashMap::default();
+        parent.insert(hash, (hash, None));
+        Side { parent, frontier: vec![(*board, empty, hash, None)] }
+    }
+
+    // hashの盤面から始点までの操作を，hashの盤面に近い順に並べる
+    fn path_from(&self, mut hash: u64) -> Vec<usize> {
+        let mut actions = vec![];
+        while let Some(&(prev, Some(action))) = self.parent.get(&hash) {
+            actions.push(action);
+            hash = prev;
+        }
+        actions
+    }
+}
+
+impl BidirectionalSearch {
+    // 盤面をemptyからtarget_emptyへ動かし，targetと同じ種類のタイルの配置にする最短の操作列を返す
+    pub fn solve(&self, board: &Board, empty: (usize, usize), target: &Board, target_empty: (usize, usize)) -> Result<Vec<char>, BidirectionalError> {
+        let start = (*board, empty);
+        let goal = (*target, target_empty);
+        let mut forward = Side::new(board, empty);
+        let mut backward = Side::new(target, target_empty);
+        if start == goal {
+            return Ok(vec![]);
+        }
+        let mut expanded = 0;
+        loop {
+            // 覚えている盤面が少ないほうを1段深くする
+            let meetings = if forward.parent.len() <= backward.parent.len() {
+                self.expand(&mut forward, &backward, &mut expanded)?
+            } else {
+                self.expand(&mut backward, &forward, &mut expanded)?
+            };
+            // 同じ段で出会った盤面でも，相手の探索での深さはまちまちなので，つないだ手順が最も短いものを選ぶ
+            if let Some(operations) = meetings.into_iter().filter_map(|hash| join(&forward, &backward, hash, start, goal)).min_by_key(|operations| operations.len()) {
+                return Ok(operations);
+            }
+            if forward.frontier.is_empty() || backward.frontier.is_empty() {
+                return Err(BidirectionalError::Unreachable);
+            }
+        }
+    }
+
+    // sideの探索を1段深くし，otherの探索で覚えている盤面と一致した盤面のハッシュを返す
+    fn expand(&self, side: &mut Side, other: &Side, expanded: &mut usize) -> Result<Vec<u64>, BidirectionalError> {
+        let mut meetings = vec![];
+        let mut next_frontier = vec![];
+        for &(state, state_empty, hash, last) in &side.frontier {
+            for action in 0..4 {
+                if last == Some(reverse_action(action)) || state.neighbor(state_empty.0, state_empty.1, action).is_none() {
+                    continue;
+                }
+                let mut next = state;
+                let next_empty = next.slide(state_empty, action);
+                let next_hash = hash ^ slide_diff(state.n, next.get(state_empty.0, state_empty.1), next_empty, state_empty);
+                if side.parent.contains_key(&next_hash) {
+                    continue;
+                }
+                side.parent.insert(next_hash, (hash, Some(action)));
+                if other.parent.contains_key(&next_hash) {
+                    meetings.push(next_hash);
+                }
+                next_frontier.push((next, next_empty, next_hash, Some(action)));
+            }
+            *expanded += 1;
+            if *expanded % TIME_CHECK_INTERVAL == 0 && Instant::now() >= self.deadline {
+                return Err(BidirectionalError::TimeLimit);
+            }
+            if side.parent.len() + other.parent.len() > self.node_limit {
+                return Err(BidirectionalError::NodeLimit);
+            }
+        }
+        side.frontier = next_frontier;
+        Ok(meetings)
+    }
+
+    // ルーターの答えのような長い操作列を，window手ずつの区間ごとに両方向探索で最短の手順に置き換えて縮める
+    // 区間の切れ目をwindow/2ずらしながら，縮まらなくなるかdeadlineを過ぎるまで繰り返す
+    pub fn shorten(&self, board: &Board, empty: (usize, usize), operations: &[char], window: usize) -> Vec<char> {
+        let mut operations = operations.to_vec();
+        let mut unchanged = 0;
+        let mut offset = 0;
+        while unchanged < 2 && window >= 2 && Instant::now() < self.deadline {
+            let shortened = self.shorten_once(board, empty, &operations, window, offset);
+            if shortened.len() < operations.len() {
+                unchanged = 0;
+            } else {
+                unchanged += 1;
+            }
+            operations = shortened;
+            offset = if offset == 0 { window / 2 } else { 0 };
+        }
+        operations
+    }
+
+    fn shorten_once(&self, board: &Board, empty: (usize, usize), operations: &[char], window: usize, offset: usize) -> Vec<char> {
+        // 各手数の時点の盤面
+        let mut states = vec![(*board, empty)];
+        for &c in operations {
+            let (mut next, next_empty) = *states.last().unwrap();
+            let next_empty = next.slide(next_empty, char_to_action(c).unwrap());
+            states.push((next, next_empty));
+        }
+        let mut bounds: Vec<usize> = (offset..operations.len()).step_by(window).collect();
+        if bounds.first() != Some(&0) {
+            bounds.insert(0, 0);
+        }
+        bounds.push(operations.len());
+        let mut shortened = vec![];
+        for w in bounds.windows(2) {
+            let (from, to) = (w[0], w[1]);
+            match self.solve(&states[from].0, states[from].1, &states[to].0, states[to].1) {
+                Ok(path) if path.len() < to - from => shortened.extend(path),
+                Ok(_) | Err(BidirectionalError::NodeLimit) | Err(BidirectionalError::Unreachable) => shortened.extend_from_slice(&operations[from..to]),
+                Err(BidirectionalError::TimeLimit) => {
+                    shortened.extend_from_slice(&operations[from..]);
+                    break;
+                }
+            }
+        }
+        shortened
+    }
+}
+
+// hashの盤面で前向きと逆向きの探索をつなぎ，startからgoalまでの操作列にする
+// ハッシュの衝突でつながっていなければNone
+fn join(forward: &Side, backward: &Side, hash: u64, start: (Board, (usize, usize)), goal: (Board, (usize, usize))) -> Option<Vec<char>> {
+    let mut actions = forward.path_from(hash);
+    actions.reverse();
+    // 逆向きの探索の操作は，目標配置側から見た向きなので逆にする
+    actions.extend(backward.path_from(hash).into_iter().map(reverse_action));
+    let (mut replayed, mut replayed_empty) = start;
+    for &action in &actions {
+        replayed_empty = replayed.slide(replayed_empty, action);
+    }
+    if (replayed, replayed_empty) != goal {
+        return None;
+    }
+    Some(actions.into_iter().map(|action| ACTION_CHARS[action]).collect())
+}
@@ -0,0 +1,23 @@
+use ahc011::board::read_input;
+use ahc011::config::{new_rng, seed, time_limit};
+use ahc011::search::SequenceAnnealing;
+use ahc011::Solver;
+use proconio::fastout;
+use std::time::Instant;
+
+// 操作列を焼きなます解法
+#[fastout]
+fn main() {
+    let start = Instant::now();
+    let input = read_input();
+    let mut solver = SequenceAnnealing {
+        max_segment_len: 4,
+        // 木のタイルが1枚増えると5000点前後変わる
+        start_temperature: 5000.0,
+        end_temperature: 100.0,
+        deadline: start + time_limit(),
+        rng: new_rng(seed()),
+    };
+    let operations = solver.solve(&input);
+    println!("{}", operations.iter().collect::<String>());
+}
@@ -0,0 +1,56 @@
+use ahc011::gen::gen;
+use std::path::PathBuf;
+use std::process::exit;
+
+// テストケースを生成して<dir>/in/NNNN.txtに書き出す
+// cargo-competeのtestcases/a.ymlがtestcases/a/in/*.txtを読みにいく
+// 使い方: gen <seed_from> <seed_to> [--n N] [--dir DIR]
+// seedはseed_from以上seed_to未満
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!("usage: {} <seed_from> <seed_to> [--n N] [--dir DIR]", args[0]);
+    let mut positional = vec![];
+    let mut n = None;
+    let mut dir = PathBuf::from("testcases/a");
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--n" if i + 1 < args.len() => {
+                n = Some(args[i + 1].parse::<usize>().unwrap_or_else(|_| {
+                    eprintln!("{}", usage);
+                    exit(2);
+                }));
+                i += 2;
+            }
+            "--dir" if i + 1 < args.len() => {
+                dir = PathBuf::from(&args[i + 1]);
+                i += 2;
+            }
+            _ => {
+                positional.push(args[i].parse::<u64>().unwrap_or_else(|_| {
+                    eprintln!("{}", usage);
+                    exit(2);
+                }));
+                i += 1;
+            }
+        }
+    }
+    if positional.len() != 2 || positional[0] > positional[1] {
+        eprintln!("{}", usage);
+        exit(2);
+    }
+
+    let in_dir = dir.join("in");
+    if let Err(e) = std::fs::create_dir_all(&in_dir) {
+        eprintln!("failed to create {}: {}", in_dir.display(), e);
+        exit(1);
+    }
+    for seed in positional[0]..positional[1] {
+        let input = gen(seed, n);
+        let path = in_dir.join(format!("{:04}.txt", seed));
+        if let Err(e) = std::fs::write(&path, input.to_string()) {
+            eprintln!("failed to write {}: {}", path.display(), e);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use ahc011::board::{parse_input, Input};
+use ahc011::evaluation::{classify_tiles, TileClass};
+use ahc011::replay::{parse_output, replay};
+use ahc011::scoring::{compute_official_tree_size, compute_score};
+use std::io::{IsTerminal, Read, Write};
+use std::process::{exit, Command, Stdio};
+use std::time::{Duration, Instant};
+
+// 入力と出力を受け取り，ターミナル上で1手ずつ盤面を再生する
+// ブラウザのないリモート環境で探索の様子をざっと眺めるため
+// 使い方: play <input> <output> [--fps F]
+// 操作: space 一時停止/再開, n/→ 1手進む, b/← 1手戻る, +/- 速度変更, q 終了
+
+// タイルの番号(左1, 上2, 右4, 下8)に対応する罫線
+const PIPES: [char; 16] = [' ', '╴', '╵', '┘', '╶', '─', '└', '┴', '╷', '┐', '│', '┤', '┌', '┬', '├', '┼'];
+
+// stty経由で端末を1文字ずつ読めるモードにし，終了時に元に戻す
+struct RawMode {
+    saved: Option<String>,
+}
+
+impl RawMode {
+    fn enable() -> RawMode {
+        if !std::io::stdin().is_terminal() {
+            return RawMode { saved: None };
+        }
+        let saved = Command::new("stty").arg("-g").stdin(Stdio::inherit()).output().ok().map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+        Command::new("stty").args(["-icanon", "-echo", "min", "0", "time", "0"]).stdin(Stdio::inherit()).status().ok();
+        RawMode { saved }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            Command::new("stty").arg(saved).stdin(Stdio::inherit()).status().ok();
+        }
+        // カーソルを表示に戻す
+        print!("\x1b[?25h");
+        std::io::stdout().flush().ok();
+    }
+}
+
+// turn手目の盤面を描画する
+fn draw(input: &Input, operation_list: &[char], turn: usize, paused: bool, fps: f64) {
+    let result = replay(input, operation_list, turn).unwrap();
+    let n = input.n;
+    let class = classify_tiles(&result.board);
+    let tree_size = compute_official_tree_size(&result.board);
+    let has_loop = class.iter().flatten().any(|&c| c == TileClass::Loop);
+    let score = compute_score(turn as f64, tree_size as f64, n as f64, input.t as f64);
+
+    let mut frame = String::from("\x1b[H\x1b[2J");
+    for i in 0..n {
+        for j in 0..n {
+            // 最大の木は緑，ループを含む連結成分は赤，空きマスは灰色で塗る
+            let (color, c) = match class[i][j] {
+                TileClass::Empty => ("\x1b[100m", ' '),
+                TileClass::Normal => ("", PIPES[result.board.get(i, j) as usize]),
+                TileClass::LargestTree => ("\x1b[32;1m", PIPES[result.board.get(i, j) as usize]),
+                TileClass::Loop => ("\x1b[31;1m", PIPES[result.board.get(i, j) as usize]),
+            };
+            // 横方向の道が途切れないよう，右に道があれば横棒で埋める
+            let fill = if result.board.get(i, j) & 4 != 0 { '─' } else { ' ' };
+            frame.push_str(&format!("{}{}{}\x1b[0m", color, c, fill));
+        }
+        frame.push_str("\r\n");
+    }
+    frame.push_str(&format!("\r\nturn {} / {} (T = {})\r\n", turn, operation_list.len(), input.t));
+    frame.push_str(&format!("tree size = {} / {}, loop = {}\r\n", tree_size, n * n - 1, has_loop));
+    frame.push_str(&format!("score = {}\r\n", score));
+    frame.push_str(&format!("{} {:.1} fps  [space] pause [n/b] step [+/-] speed [q] quit\r\n", if paused { "paused " } else { "playing" }, fps));
+    print!("{}", frame);
+    std::io::stdout().flush().ok();
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!("usage: {} <input> <output> [--fps F]", args[0]);
+    let mut paths = vec![];
+    let mut fps = 10.0;
+    let mut i = 1;
+    while i < args.len() {
+        if args[i] == "--fps" && i + 1 < args.len() {
+            // 0以下やNaNだと1フレームの時間が計算できないので弾く
+            fps = match args[i + 1].parse::<f64>() {
+                Ok(fps) if fps.is_finite() && fps > 0.0 => fps,
+                _ => {
+                    eprintln!("{}", usage);
+                    exit(2);
+                }
+            };
+            i += 2;
+        } else {
+            paths.push(args[i].clone());
+            i += 1;
+        }
+    }
+    if paths.len() != 2 {
+        eprintln!("{}", usage);
+        exit(2);
+    }
+    let read = |path: &str| {
+        std::fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("failed to read {}: {}", path, e);
+            exit(2);
+        })
+    };
+    let input = parse_input(&read(&paths[0]));
+    let operation_list = parse_output(&read(&paths[1]));
+    // 不正な出力は再生前に弾く
+    if let Err(e) = replay(&input, &operation_list, operation_list.len()) {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+
+    let _raw_mode = RawMode::enable();
+    print!("\x1b[?25l");
+    let mut turn = 0;
+    let mut paused = false;
+    let mut last_step = Instant::now();
+    let mut stdin = std::io::stdin();
+    let mut buf = [0_u8; 8];
+    draw(&input, &operation_list, turn, paused, fps);
+    loop {
+        // キー入力を処理する(入力がなければすぐに0が返る)
+        let mut redraw = false;
+        let len = if std::io::stdin().is_terminal() { stdin.read(&mut buf).unwrap_or(0) } else { 0 };
+        match &buf[..len] {
+            b"q" => break,
+            b" " => paused = !paused,
+            b"n" | b"\x1b[C" => {
+                paused = true;
+                turn = (turn + 1).min(operation_list.len());
+            }
+            b"b" | b"\x1b[D" => {
+                paused = true;
+                turn = turn.saturating_sub(1);
+            }
+            b"+" => fps = (fps * 2.0).min(1000.0),
+            b"-" => fps = (fps / 2.0).max(0.5),
+            _ => {}
+        }
+        if len > 0 {
+            redraw = true;
+        }
+        if !paused && last_step.elapsed() >= Duration::from_secs_f64(1.0 / fps) {
+            last_step = Instant::now();
+            if turn < operation_list.len() {
+                turn += 1;
+                redraw = true;
+            } else if !std::io::stdin().is_terminal() {
+                // パイプなどで操作できないときは最後まで再生して終わる
+                break;
+            }
+        }
+        if redraw {
+            draw(&input, &operation_list, turn, paused, fps);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
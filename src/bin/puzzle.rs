@@ -0,0 +1,31 @@
+use ahc011::arrangement::ArrangementAnnealing;
+use ahc011::board::read_input;
+use ahc011::config::{new_rng, seed, time_limit};
+use ahc011::search::PuzzleSearch;
+use ahc011::Solver;
+use proconio::fastout;
+use std::time::Instant;
+
+// 目標配置を決めてからスライドパズルとして動かす解法
+// 手数をTと比べられるよう，標準エラーに出す
+#[fastout]
+fn main() {
+    let start = Instant::now();
+    let input = read_input();
+    let n = input.n;
+    let mut solver = PuzzleSearch {
+        annealing: ArrangementAnnealing {
+            empty: (n - 1, n - 1),
+            start_temperature: 0.5,
+            end_temperature: 0.01,
+            deadline: start + time_limit(),
+            budget: time_limit() / 3,
+            rng: new_rng(seed()),
+        },
+        shortcut_window: 16,
+        last_rows_node_limit: 2_000_000,
+    };
+    let operations = solver.solve(&input);
+    eprintln!("moves = {} / {}", operations.len(), input.t);
+    println!("{}", operations.iter().collect::<String>());
+}
@@ -0,0 +1,17 @@
+use ahc011::board::read_input;
+use ahc011::config::{new_rng, seed, time_limit};
+use ahc011::search::RowByRowSearch;
+use ahc011::Solver;
+use proconio::fastout;
+use std::time::Instant;
+
+// 1行ずつ木を作りながら揃える解法
+#[fastout]
+fn main() {
+    let start = Instant::now();
+    let input = read_input();
+    let mut solver = RowByRowSearch { deadline: start + time_limit(), rng: new_rng(seed()) };
+    let operations = solver.solve(&input);
+    eprintln!("moves = {} / {}", operations.len(), input.t);
+    println!("{}", operations.iter().collect::<String>());
+}
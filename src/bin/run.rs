@@ -0,0 +1,220 @@
+use ahc011::board::parse_input;
+use ahc011::replay::{parse_output, replay};
+use ahc011::scoring::{compute_official_tree_size, compute_score};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{exit, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// 指定した解法のbinを<dir>/in/*.txtの全ケースに対して全コアで並列に実行し，
+// 1ケースごとの得点･実行時間･ピークメモリ(2msごとに見たもの)をCSVに，Nごとの集計を標準出力に書き出す
+// 使い方: run <solver> [--dir DIR] [--out DIR] [--time-limit-ms MS] [--jobs N]
+
+// 1ケースの実行結果
+struct CaseResult {
+    name: String,
+    n: usize,
+    score: i32,
+    tree_size: i32,
+    moves: usize,
+    time_ms: u128,
+    // 実行中に2msごとに読んだVmHWMの最大．最後に読んでから終了するまでに増えたぶんは入らない
+    sampled_peak_rss_kb: u64,
+    status: String,
+}
+
+// /proc/<pid>/statusからピークメモリ(VmHWM)をkB単位で読む
+// Linux以外や読めなかったときは0を返す
+fn read_peak_rss_kb(pid: u32) -> u64 {
+    let status = match std::fs::read_to_string(format!("/proc/{}/status", pid)) {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+    status
+        .lines()
+        .find(|line| line.starts_with("VmHWM:"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse().ok())
+        .unwrap_or(0)
+}
+
+// 1ケースを実行して得点をつける
+// 時間制限を超えたらkillしてTLEとする
+fn run_case(solver: &Path, input_path: &Path, out_dir: &Path, time_limit: Duration) -> CaseResult {
+    let name = input_path.file_stem().unwrap().to_string_lossy().to_string();
+    let input_str = std::fs::read_to_string(input_path).unwrap();
+    let input = parse_input(&input_str);
+    let mut result = CaseResult { name: name.clone(), n: input.n, score: 0, tree_size: 0, moves: 0, time_ms: 0, sampled_peak_rss_kb: 0, status: String::new() };
+
+    let stderr_file = std::fs::File::create(out_dir.join(format!("{}.err", name))).unwrap();
+    let start = Instant::now();
+    let mut child = match Command::new(solver).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(stderr_file).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            result.status = format!("RE: {}", e);
+            return result;
+        }
+    };
+    // 入力を読まずに終了した解法にはEPIPEで書けないので，失敗したケースとして記録する
+    if let Err(e) = child.stdin.take().unwrap().write_all(input_str.as_bytes()) {
+        child.kill().ok();
+        child.wait().ok();
+        result.time_ms = start.elapsed().as_millis();
+        result.status = format!("RE: failed to write input: {}", e);
+        return result;
+    }
+    // 出力がパイプに詰まらないよう，別スレッドで読み続ける
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut s = String::new();
+        stdout.read_to_string(&mut s).map(|_| s)
+    });
+    // 終了を待ちながら，ピークメモリを見張る
+    let pid = child.id();
+    let exit_status = loop {
+        result.sampled_peak_rss_kb = result.sampled_peak_rss_kb.max(read_peak_rss_kb(pid));
+        if let Some(exit_status) = child.try_wait().unwrap() {
+            break Some(exit_status);
+        }
+        if start.elapsed() > time_limit {
+            child.kill().ok();
+            child.wait().ok();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(2));
+    };
+    result.time_ms = start.elapsed().as_millis();
+    let output = reader.join().unwrap().unwrap_or_default();
+    std::fs::write(out_dir.join(format!("{}.txt", name)), &output).unwrap();
+
+    match exit_status {
+        None => {
+            result.status = "TLE".to_string();
+            return result;
+        }
+        Some(exit_status) if !exit_status.success() => {
+            result.status = format!("RE: {}", exit_status);
+            return result;
+        }
+        Some(_) => {}
+    }
+    let operation_list = parse_output(&output);
+    match replay(&input, &operation_list, operation_list.len()) {
+        Ok(replayed) => {
+            result.tree_size = compute_official_tree_size(&replayed.board);
+            result.moves = replayed.turn;
+            result.score = compute_score(result.moves as f64, result.tree_size as f64, input.n as f64, input.t as f64);
+            result.status = "AC".to_string();
+        }
+        Err(e) => result.status = format!("WA: {}", e),
+    }
+    result
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!("usage: {} <solver> [--dir DIR] [--out DIR] [--time-limit-ms MS] [--jobs N]", args[0]);
+    let mut solver = None;
+    let mut dir = PathBuf::from("testcases/a");
+    let mut out_dir = None;
+    let mut time_limit_ms = 3000;
+    let mut jobs = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut i = 1;
+    while i < args.len() {
+        match (args[i].as_str(), args.get(i + 1)) {
+            ("--dir", Some(value)) => dir = PathBuf::from(value),
+            ("--out", Some(value)) => out_dir = Some(PathBuf::from(value)),
+            ("--time-limit-ms", Some(value)) => time_limit_ms = value.parse().unwrap_or_else(|_| {
+                eprintln!("{}", usage);
+                exit(2);
+            }),
+            ("--jobs", Some(value)) => jobs = value.parse().unwrap_or_else(|_| {
+                eprintln!("{}", usage);
+                exit(2);
+            }),
+            (value, _) if solver.is_none() && !value.starts_with("--") => {
+                solver = Some(PathBuf::from(value));
+                i += 1;
+                continue;
+            }
+            _ => {
+                eprintln!("{}", usage);
+                exit(2);
+            }
+        }
+        i += 2;
+    }
+    let solver = match solver {
+        Some(solver) => solver,
+        None => {
+            eprintln!("{}", usage);
+            exit(2);
+        }
+    };
+    // 出力先は解法ごとに分ける
+    let out_dir = out_dir.unwrap_or_else(|| dir.join(format!("out_{}", solver.file_name().unwrap().to_string_lossy())));
+    std::fs::create_dir_all(&out_dir).unwrap();
+
+    let mut input_paths: Vec<PathBuf> = match std::fs::read_dir(dir.join("in")) {
+        Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.extension().is_some_and(|ext| ext == "txt")).collect(),
+        Err(e) => {
+            eprintln!("failed to read {}: {}", dir.join("in").display(), e);
+            exit(1);
+        }
+    };
+    input_paths.sort();
+
+    // 未実行のケースを共有のキューに積み，各スレッドが1つずつ取り出して実行する
+    let queue = Arc::new(Mutex::new(input_paths.into_iter().rev().collect::<Vec<_>>()));
+    let results = Arc::new(Mutex::new(vec![]));
+    let time_limit = Duration::from_millis(time_limit_ms);
+    let handles: Vec<_> = (0..jobs.max(1))
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let results = Arc::clone(&results);
+            let solver = solver.clone();
+            let out_dir = out_dir.clone();
+            std::thread::spawn(move || loop {
+                let input_path = match queue.lock().unwrap().pop() {
+                    Some(path) => path,
+                    None => break,
+                };
+                let result = run_case(&solver, &input_path, &out_dir, time_limit);
+                eprintln!("{} N={} score={} time={}ms sampled_rss={}kB {}", result.name, result.n, result.score, result.time_ms, result.sampled_peak_rss_kb, result.status);
+                results.lock().unwrap().push(result);
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let mut results = Arc::try_unwrap(results).ok().unwrap().into_inner().unwrap();
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // ケースごとの結果をCSVに書き出す
+    let mut csv = String::from("case,n,score,tree_size,moves,time_ms,sampled_peak_rss_kb,status\n");
+    for r in &results {
+        csv += &format!("{},{},{},{},{},{},{},\"{}\"\n", r.name, r.n, r.score, r.tree_size, r.moves, r.time_ms, r.sampled_peak_rss_kb, r.status.replace('"', "'"));
+    }
+    let csv_path = out_dir.join("result.csv");
+    std::fs::write(&csv_path, csv).unwrap();
+
+    // Nごとの集計
+    let mut by_n: BTreeMap<usize, Vec<&CaseResult>> = BTreeMap::new();
+    for r in &results {
+        by_n.entry(r.n).or_default().push(r);
+    }
+    println!("{:>3} {:>5} {:>10} {:>8} {:>4} {:>8} {:>19}", "N", "cases", "mean", "min", "TLE", "max_ms", "max_rss_kB(sampled)");
+    for (n, rs) in &by_n {
+        let mean = rs.iter().map(|r| r.score as f64).sum::<f64>() / rs.len() as f64;
+        let min = rs.iter().map(|r| r.score).min().unwrap();
+        let tle = rs.iter().filter(|r| r.status == "TLE").count();
+        let max_ms = rs.iter().map(|r| r.time_ms).max().unwrap();
+        let max_rss = rs.iter().map(|r| r.sampled_peak_rss_kb).max().unwrap();
+        println!("{:>3} {:>5} {:>10.1} {:>8} {:>4} {:>8} {:>19}", n, rs.len(), mean, min, tle, max_ms, max_rss);
+    }
+    let total: i64 = results.iter().map(|r| r.score as i64).sum();
+    println!("total score = {} ({} cases), csv: {}", total, results.len(), csv_path.display());
+}
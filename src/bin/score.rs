@@ -0,0 +1,46 @@
+use ahc011::board::parse_input;
+use ahc011::evaluation::count_dangling;
+use ahc011::replay::{parse_output, replay};
+use ahc011::scoring::{compute_official_tree_size, compute_score};
+use std::process::exit;
+
+// 入力と出力(UDLRの操作列)を受け取り，1手ずつ再生して得点を計算する
+// 使い方: score <input> <output>
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} <input> <output>", args[0]);
+        exit(2);
+    }
+    let input = match std::fs::read_to_string(&args[1]) {
+        Ok(s) => parse_input(&s),
+        Err(e) => {
+            eprintln!("failed to read {}: {}", args[1], e);
+            exit(2);
+        }
+    };
+    let operation_list = match std::fs::read_to_string(&args[2]) {
+        Ok(s) => parse_output(&s),
+        Err(e) => {
+            eprintln!("failed to read {}: {}", args[2], e);
+            exit(2);
+        }
+    };
+
+    let result = match replay(&input, &operation_list, operation_list.len()) {
+        Ok(result) => result,
+        Err(e) => {
+            // 不正な出力は公式と同じく0点とする
+            eprintln!("error: {}", e);
+            println!("Score = 0");
+            exit(1);
+        }
+    };
+    let tree_size = compute_official_tree_size(&result.board);
+    let score = compute_score(result.turn as f64, tree_size as f64, input.n as f64, input.t as f64);
+    println!("Score = {}", score);
+    eprintln!("tree size = {} / {}", tree_size, input.n * input.n - 1);
+    eprintln!("moves = {} / {}", result.turn, input.t);
+    let dangling = count_dangling(&result.board);
+    eprintln!("dangling edges = {} (border {}, mismatched {})", dangling.total(), dangling.border, dangling.mismatched);
+}
@@ -0,0 +1,50 @@
+use ahc011::board::parse_input;
+use ahc011::replay::{parse_output, replay};
+use ahc011::svg::render_svg;
+use std::process::exit;
+
+// 入力(と出力)から盤面をSVGで描画して標準出力に書き出す
+// --turnを指定すればその手番の盤面を，指定しなければ最後の盤面を描く
+// 使い方: vis <input> [<output>] [--turn K] > board.svg
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!("usage: {} <input> [<output>] [--turn K]", args[0]);
+    let mut paths = vec![];
+    let mut turn = None;
+    let mut i = 1;
+    while i < args.len() {
+        if args[i] == "--turn" && i + 1 < args.len() {
+            turn = Some(args[i + 1].parse::<usize>().unwrap_or_else(|_| {
+                eprintln!("{}", usage);
+                exit(2);
+            }));
+            i += 2;
+        } else {
+            paths.push(args[i].clone());
+            i += 1;
+        }
+    }
+    if paths.is_empty() || paths.len() > 2 {
+        eprintln!("{}", usage);
+        exit(2);
+    }
+    let read = |path: &str| {
+        std::fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("failed to read {}: {}", path, e);
+            exit(2);
+        })
+    };
+    let input = parse_input(&read(&paths[0]));
+    let operation_list = match paths.get(1) {
+        Some(path) => parse_output(&read(path)),
+        None => vec![],
+    };
+    let turn = turn.unwrap_or(operation_list.len());
+    match replay(&input, &operation_list, turn) {
+        Ok(result) => print!("{}", render_svg(&result.board)),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+// 盤面の状態と，盤面を動かす操作
+use crate::evaluation::{count_dangling, dangling_around, Dangling};
+use crate::incremental::IncrementalComponents;
+use crate::zobrist::{hash_board, slide_diff};
+use crate::tiles::{char_to_tile, reverse_action, tile_to_char, ACTION_CHARS, DIR_BITS, DX, DY};
+use ordered_float::OrderedFloat;
+use proconio::source::once::OnceSource;
+use proconio::{input, marker};
+use std::cmp::Ordering;
+use std::fmt;
+
+// 問題の入力
+#[derive(Clone, Debug)]
+pub struct Input {
+    pub n: usize,
+    pub t: usize,
+    pub board: Vec<Vec<char>>,
+}
+
+// 入力と同じ形式で書き出す
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} {}", self.n, self.t)?;
+        for line in &self.board {
+            writeln!(f, "{}", line.iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+// 標準入力から入力を受け取る
+pub fn read_input() -> Input {
+    input!{
+        n: usize,
+        t: usize,
+        board: [marker::Chars; n],
+    }
+    Input { n, t, board }
+}
+
+// 文字列から入力を受け取る
+// ファイルに保存した入力をスコア計算などで読み直すときに使う
+pub fn parse_input(s: &str) -> Input {
+    input!{
+        from OnceSource::from(s),
+        n: usize,
+        t: usize,
+        board: [marker::Chars; n],
+    }
+    Input { n, t, board }
+}
+
+// 盤面のNの最大値
+pub const MAX_N: usize = 10;
+
+// NxNの盤面
+// 各マスにタイルのビット(左1, 上2, 右4, 下8, 空きマスは0)を1つずつ持つ
+// 状態をたくさん持てるよう，ヒープを使わない固定長の配列にする
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Board {
+    pub n: usize,
+    pub tiles: [u8; MAX_N * MAX_N],
+}
+
+impl Board {
+    // 入力の文字の盤面からBoardを作る
+    // そのついでに空きタイルの初期位置を返す
+    pub fn from_chars(board: &[Vec<char>]) -> (Board, (usize, usize)) {
+        let n = board.len();
+        let mut tiles = [0; MAX_N * MAX_N];
+        let mut empty = (0_usize, 0_usize);
+        for i in 0..n {
+            for j in 0..n {
+                if board[i][j] == '0' {
+                    empty = (i, j);
+                }
+                tiles[i * n + j] = char_to_tile(board[i][j]);
+            }
+        }
+        (Board { n, tiles }, empty)
+    }
+
+    // 入力と同じ文字の盤面に戻す
+    pub fn to_chars(&self) -> Vec<Vec<char>> {
+        (0..self.n).map(|i| (0..self.n).map(|j| tile_to_char(self.get(i, j))).collect()).collect()
+    }
+
+    // (i, j)のタイル
+    pub fn get(&self, i: usize, j: usize) -> u8 {
+        self.tiles[i * self.n + j]
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, tile: u8) {
+        self.tiles[i * self.n + j] = tile;
+    }
+
+    // (i, j)のdir方向の隣のマス．盤面の外ならNone
+    pub fn neighbor(&self, i: usize, j: usize, dir: usize) -> Option<(usize, usize)> {
+        let x = i as i32 + DX[dir];
+        let y = j as i32 + DY[dir];
+        if x < 0 || x >= self.n as i32 || y < 0 || y >= self.n as i32 {
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+
+    // (i, j)とそのdir方向の隣のタイルが道でつながっているか
+    // 両方のタイルに向かい合う道があるときだけ辺があるとみなす
+    pub fn is_connected(&self, i: usize, j: usize, dir: usize) -> bool {
+        if self.get(i, j) & DIR_BITS[dir] == 0 {
+            return false;
+        }
+        match self.neighbor(i, j, dir) {
+            Some((x, y)) => self.get(x, y) & DIR_BITS[reverse_action(dir)] != 0,
+            None => false,
+        }
+    }
+
+    // 空きマスのaction方向にあるタイルを空きマスに引っ張ってくる
+    // 移動後の空きマスの座標を返す
+    pub fn slide(&mut self, empty: (usize, usize), action: usize) -> (usize, usize) {
+        let next_to_empty_x = (empty.0 as i32 + DX[action]) as usize;
+        let next_to_empty_y = (empty.1 as i32 + DY[action]) as usize;
+        let tile = self.get(next_to_empty_x, next_to_empty_y);
+        self.set(empty.0, empty.1, tile);
+        self.set(next_to_empty_x, next_to_empty_y, 0);
+        (next_to_empty_x, next_to_empty_y)
+    }
+}
+
+// 合法手を取得する
+// 前回の手を相殺する手と，範囲外参照を防ぐ
+pub fn get_legal_actions(operation_list: &[char], empty: (usize, usize), n: usize) -> Vec<usize> {
+    let last = operation_list.last().copied();
+    (0..4).filter(|&action| is_legal_action(last, empty, action, n)).collect()
+}
+
+// 直前の手がlastのとき，actionが合法手か
+// 1手ずつ判定したいときに，合法手の一覧を作らずに済ませる
+pub fn is_legal_action(last: Option<char>, empty: (usize, usize), action: usize, n: usize) -> bool {
+    last != Some(ACTION_CHARS[reverse_action(action)]) && can_slide(empty, action, n)
+}
+
+// 空きマスのaction方向にタイルがあるか(盤面の外に出ないか)
+pub fn can_slide(empty: (usize, usize), action: usize, n: usize) -> bool {
+    !(empty.0 as i32 + DX[action] < 0 || (empty.0 as i32 + DX[action]) as usize > n-1 || empty.1 as i32 + DY[action] < 0 || (empty.1 as i32 + DY[action]) as usize > n-1)
+}
+
+// 手順を1手2ビットで詰めて持つ
+// GameStateはビームサーチで何度も複製されるので，手順の複製を軽くしておく
+// 出力するときだけto_charsで文字にする
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Moves {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Moves {
+    pub fn new() -> Moves {
+        Moves::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, action: usize) {
+        let (word, shift) = (self.len / 32, self.len % 32 * 2);
+        if word == self.words.len() {
+            self.words.push(0);
+        }
+        self.words[word] |= (action as u64) << shift;
+        self.len += 1;
+    }
+
+    // i手目の方向
+    pub fn get(&self, i: usize) -> usize {
+        (self.words[i / 32] >> (i % 32 * 2) & 3) as usize
+    }
+
+    pub fn last(&self) -> Option<usize> {
+        self.len.checked_sub(1).map(|i| self.get(i))
+    }
+
+    // 先頭のlen手だけ残す
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        self.len = len;
+        self.words.truncate(len.div_ceil(32));
+        // 残した最後の語の，len手目以降のビットを消す
+        let shift = len % 32 * 2;
+        if shift > 0 {
+            self.words[len / 32] &= (1 << shift) - 1;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).map(move |i| self.get(i))
+    }
+
+    pub fn to_chars(&self) -> Vec<char> {
+        self.iter().map(|action| ACTION_CHARS[action]).collect()
+    }
+}
+
+// 盤面の状態を保持する構造体
+// なるべくよい(tree_sizeの大きい)状態をビームサーチの幅数分保持し，探索を進めたい
+#[derive(Eq, Clone, Debug)]
+pub struct GameState {
+    pub n: usize,
+    pub board: Board,
+    pub empty: (usize, usize),
+    pub tree_size: i32,
+    pub has_loop: bool,
+    // 盤面全体で最大の木(ループを持たない連結成分)の大きさ．公式の得点はこれで決まる
+    pub max_tree_size: i32,
+    pub operation_list: Moves,
+    pub turn: usize,
+    // 探索側がEvaluatorで計算して入れる評価値
+    pub evaluated_score: OrderedFloat<f64>,
+    pub rectangle_area: i32,
+    // 差分更新する盤面全体の連結成分
+    pub components: IncrementalComponents,
+    // 盤面全体のつながる相手のいない道の数
+    pub dangling: Dangling,
+    // 盤面(タイルの配置と空きマスの位置)のZobrist hash
+    pub hash: u64,
+}
+// GameStateをpriority_queueに入れるとき，evaluated_scoreの大きい順に取り出すため，Ordを実装する
+impl PartialOrd for GameState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for GameState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.evaluated_score.cmp(&other.evaluated_score)
+    }
+}
+impl PartialEq for GameState {
+    fn eq(&self, other: &Self) -> bool {
+        self.evaluated_score == other.evaluated_score
+    }
+}
+impl GameState {
+    // 入力から最初の盤面を作る
+    // 木の大きさなどは最初の手を打つまで0としておく
+    pub fn new(input: &Input) -> GameState {
+        let (board, empty) = Board::from_chars(&input.board);
+        let components = IncrementalComponents::new(&board);
+        let hash = hash_board(&board, empty);
+        let dangling = count_dangling(&board);
+        GameState {
+            n: input.n,
+            board,
+            empty,
+            tree_size: 0,
+            has_loop: false,
+            max_tree_size: 0,
+            operation_list: Moves::new(),
+            turn: 0,
+            evaluated_score: OrderedFloat(0.0),
+            rectangle_area: 0,
+            components,
+            dangling,
+            hash,
+        }
+    }
+
+    // 合法手を取得する
+    pub fn get_legal_actions(&self) -> Vec<usize> {
+        let last = self.operation_list.last().map(|action| ACTION_CHARS[action]);
+        (0..4).filter(|&action| is_legal_action(last, self.empty, action, self.n)).collect()
+    }
+
+    // actionを受けてstateをひとつ進める
+    // 評価に使う木の大きさとループは，いま動かしたタイル(元の空きマス)を含む木について調べる
+    // 出力する手順を選ぶときは，盤面全体で最大の木(max_tree_size)を使う
+    // evaluated_scoreは更新しないので，探索側でEvaluatorを使って入れること
+    pub fn advance(&mut self, action: usize) {
+        let moved = self.empty;
+        // 動かすタイルの周りだけ，つながる相手のいない道を数え直す
+        let from = ((moved.0 as i32 + DX[action]) as usize, (moved.1 as i32 + DY[action]) as usize);
+        let before = dangling_around(&self.board, moved, from);
+        self.empty = self.board.slide(self.empty, action);
+        self.dangling = self.dangling - before + dangling_around(&self.board, moved, self.empty);
+        self.hash ^= slide_diff(self.n, self.board.get(moved.0, moved.1), self.empty, moved);
+        // 動かしたタイルを含む木の大きさと，ループがあるかを調べる
+        // 2マス分の差分だけ連結成分を更新する
+        self.components.update_after_slide(&self.board, moved, self.empty);
+        let component = self.components.component_at(moved.0, moved.1).unwrap();
+        self.has_loop = component.has_cycle;
+        self.tree_size = component.tile_count as i32;
+        self.rectangle_area = component.rectangle_area();
+        self.max_tree_size = self.components.max_tree_size();
+        self.operation_list.push(action);
+        self.turn += 1;
+    }
+}
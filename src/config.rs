@@ -0,0 +1,92 @@
+// 実行時に環境変数から変えられる設定
+use crate::evaluator::{Evaluator, LegacyEvaluator, WeightedEvaluator, Weights};
+use crate::SolverRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+// 探索に使う時間の既定値
+// 実行時間制限は3sだが，システスが怖いので2500ms程度に抑える
+pub const DEFAULT_TIME_LIMIT_MS: u64 = 2500;
+
+// 回数で打ち切るときの締め切り(1日)
+const NO_TIME_LIMIT_SECS: u64 = 24 * 60 * 60;
+
+// 探索で展開済みの盤面を覚えておく数の既定値
+// 1件あたり十数バイトなので，4M件でも100MB程度に収まる
+pub const DEFAULT_SEEN_CAPACITY: usize = 1 << 22;
+
+// 探索に使う時間
+// 環境変数AHC011_TIME_LIMIT_MSで上書きできる
+pub fn time_limit() -> Duration {
+    let ms = std::env::var("AHC011_TIME_LIMIT_MS").ok().and_then(|s| s.parse().ok()).unwrap_or(DEFAULT_TIME_LIMIT_MS);
+    Duration::from_millis(ms)
+}
+
+// 乱数のシード
+// 引数--seed N，環境変数AHC011_SEEDの順に見て，どちらもなければランダムに決める
+// 悪いケースを再現できるよう，使ったシードは必ず標準エラーに出す
+// 時刻で打ち切る解法は回った回数が実行ごとに変わるので，同じ出力を再現したいときはiterationsも指定する
+pub fn seed() -> u64 {
+    let args: Vec<String> = std::env::args().collect();
+    let from_args = args.iter().position(|a| a == "--seed").and_then(|i| args.get(i + 1)).and_then(|s| s.parse().ok());
+    let from_env = || std::env::var("AHC011_SEED").ok().and_then(|s| s.parse().ok());
+    let seed = from_args.or_else(from_env).unwrap_or_else(|| rand::thread_rng().gen());
+    eprintln!("seed = {}", seed);
+    seed
+}
+
+// 時刻の代わりに探索の回数(chokudaiサーチなら浅いところからやり直す回数)で打ち切るときの回数
+// 引数--iterations K，環境変数AHC011_ITERATIONSの順に見る
+// 指定すると時間制限を使わないので，同じシードと合わせれば出力が1バイトも変わらない
+pub fn iterations() -> Option<usize> {
+    let args: Vec<String> = std::env::args().collect();
+    let value = match args.iter().position(|a| a == "--iterations") {
+        Some(i) => args.get(i + 1).cloned(),
+        None => std::env::var("AHC011_ITERATIONS").ok(),
+    }?;
+    match value.parse() {
+        Ok(iterations) => {
+            eprintln!("iterations = {}", iterations);
+            Some(iterations)
+        }
+        Err(_) => {
+            eprintln!("--iterations / AHC011_ITERATIONS: expected a non-negative integer, got {:?}", value);
+            eprintln!("usage: {} [--seed N] [--iterations K] < input", args[0]);
+            std::process::exit(2);
+        }
+    }
+}
+
+// 探索を打ち切る時刻
+// iterationsを指定したときは回数だけで打ち切るので，来ることのない時刻にする
+pub fn deadline(start: Instant, iterations: Option<usize>) -> Instant {
+    match iterations {
+        Some(_) => start + Duration::from_secs(NO_TIME_LIMIT_SECS),
+        None => start + time_limit(),
+    }
+}
+
+// シードから探索用の乱数生成機を作る
+pub fn new_rng(seed: u64) -> SolverRng {
+    SolverRng::seed_from_u64(seed)
+}
+
+// 探索で使う評価関数
+// 環境変数AHC011_WEIGHTSに"tree_size=1,cycle_count=-0.5"のように重みを書けば特徴量の重み付き和を使い，
+// なければ提出していた評価関数を使う．書き間違えていたら使い方を出して終了する
+pub fn evaluator() -> Box<dyn Evaluator> {
+    match std::env::var("AHC011_WEIGHTS") {
+        Ok(s) => match Weights::parse(&s) {
+            Ok(weights) => {
+                eprintln!("weights = {:?}", weights);
+                Box::new(WeightedEvaluator { weights })
+            }
+            Err(e) => {
+                eprintln!("AHC011_WEIGHTS: {}", e);
+                eprintln!("usage: AHC011_WEIGHTS=\"tree_size=1,cycle_count=-0.5\" (features: tree_size, max_tree_size, cycle_count, cycle_length, fill_ratio, component_count, dangling, turn)");
+                std::process::exit(2);
+            }
+        },
+        Err(_) => Box::new(LegacyEvaluator),
+    }
+}
@@ -0,0 +1,289 @@
+// 1行ずつ木を作りながらタイルを運んで固定する構築的な解法
+// 20220603のメモの「任意のタイルを任意の位置に動かす」方針で，目標配置を先に決めずに1行ずつ決める
+// 上の行から1行ずつ，その行に置くタイルの種類を，固定したタイルと道が食い違わず，ループができず，
+// どの連結成分にもまだ固定していないマスへ伸びる道が残るように選び，固定したマスを通らずに運んで固定する
+// 最後の2行は左の列から2マスずつ同じように選び，残った右下の2x3は，たどり着ける並びのうち木が最も大きいものにする
+// タイルはpuzzle::Routerで運び，Routerが動かした手をGameState::advanceで盤面に反映する
+use crate::board::{Board, GameState, Input, MAX_N};
+use crate::dsu::UnionFind;
+use crate::puzzle::{RouteError, Router};
+use crate::scoring::compute_official_tree_size;
+use crate::tiles::{reverse_action, DIR_BITS};
+use crate::SolverRng;
+use rand::Rng;
+use std::time::Instant;
+
+// 1グループのタイルの種類の選び方を探すときに，試す選び方の数の上限
+const CHOICE_NODE_LIMIT: usize = 20_000;
+
+// 入力の盤面から1行ずつ揃えた盤面を作る
+// 種類の選び方の同点はrngで崩すので，何度も呼べば違う盤面になる
+// 締め切りを過ぎたら次の行(列)には進まず，そこまで揃えた盤面を返す
+pub fn construct(input: &Input, rng: &mut SolverRng, deadline: Instant) -> Result<GameState, RouteError> {
+    let n = input.n;
+    let mut builder = Builder::new(input);
+    // 上の行から1行ずつ
+    for r in 0..n.saturating_sub(2) {
+        if Instant::now() >= deadline {
+            return Ok(builder.state);
+        }
+        builder.build_row(r, rng)?;
+    }
+    // 最後の2行は左の列から1列ずつ
+    for c in 0..n.saturating_sub(3) {
+        if Instant::now() >= deadline {
+            return Ok(builder.state);
+        }
+        builder.build_column(c, rng)?;
+    }
+    builder.finish();
+    Ok(builder.state)
+}
+
+// 種類を選びながら，タイルはpuzzle::Routerで運んで固定する
+// Routerが動かした手をstateに順に反映する
+pub struct Builder {
+    pub state: GameState,
+    // 固定したマスと，種類を決めたマスの置くタイル(router.target)を持つ．まだ決めていないマスは0
+    pub router: Router,
+}
+
+impl Builder {
+    pub fn new(input: &Input) -> Builder {
+        let state = GameState::new(input);
+        let undecided = Board { n: input.n, tiles: [0; MAX_N * MAX_N] };
+        let router = Router::new(&state.board, state.empty, &undecided, None);
+        Builder { state, router }
+    }
+
+    // 上からr行目に置くタイルの種類を選び，運んで固定する
+    pub fn build_row(&mut self, r: usize, rng: &mut SolverRng) -> Result<(), RouteError> {
+        let n = self.state.n;
+        let cells: Vec<(usize, usize)> = (0..n).map(|c| (r, c)).collect();
+        self.choose(&cells, rng);
+        let result = (0..n - 2)
+            .try_for_each(|c| self.router.place((r, c), (r, c)))
+            .and_then(|_| self.router.place_pair((r, n - 2), (r, n - 1), (r + 1, n - 1), [1, 0]));
+        self.advance_all();
+        result
+    }
+
+    // 最後の2行の左からc列目に置くタイルの種類を選び，運んで固定する
+    pub fn build_column(&mut self, c: usize, rng: &mut SolverRng) -> Result<(), RouteError> {
+        let n = self.state.n;
+        self.choose(&[(n - 2, c), (n - 1, c)], rng);
+        let result = self.router.place_pair((n - 2, c), (n - 1, c), (n - 1, c + 1), [0, 1]);
+        self.advance_all();
+        result
+    }
+
+    // 固定していない右下の2x3を，たどり着ける並びのうち木が最も大きいものにする
+    pub fn finish(&mut self) {
+        self.router.solve_last_block_by(compute_official_tree_size);
+        self.advance_all();
+    }
+
+    // Routerが動かした手のうち，まだstateに反映していないものを反映する
+    fn advance_all(&mut self) {
+        for &action in &self.router.operations[self.state.turn..] {
+            self.state.advance(action);
+        }
+    }
+
+    fn fixed(&self, p: usize) -> Option<u8> {
+        match self.router.target.tiles[p] {
+            0 => None,
+            tile => Some(tile),
+        }
+    }
+
+    fn set_fixed(&mut self, p: usize, tile: Option<u8>) {
+        self.router.target.tiles[p] = tile.unwrap_or(0);
+    }
+
+    // cellsに置くタイルの種類を順に決め，router.targetに書き込む
+    // 制約をすべて満たす選び方が見つからなければ，食い違う道ができるだけ少ない種類を1マスずつ選ぶ
+    fn choose(&mut self, cells: &[(usize, usize)], rng: &mut SolverRng) {
+        let mut pool = self.pool();
+        let mut uf = self.components();
+        let mut nodes = 0;
+        if !self.choose_dfs(cells, &mut pool, &uf, &mut vec![], &mut nodes, rng) {
+            for &cell in cells {
+                self.choose_greedy(cell, &mut pool, &mut uf);
+            }
+        }
+    }
+
+    // 固定していないマスにあるタイルの種類ごとの枚数
+    fn pool(&self) -> [usize; 16] {
+        let n = self.state.n;
+        let mut pool = [0; 16];
+        for p in (0..n * n).filter(|&p| !self.router.locked[p]) {
+            pool[self.state.board.tiles[p] as usize] += 1;
+        }
+        pool[0] = 0;
+        pool
+    }
+
+    // 種類を決めたマスの，道でつながった連結成分
+    fn components(&self) -> UnionFind {
+        let n = self.state.n;
+        let mut uf = UnionFind::new(n * n);
+        for p in (0..n * n).filter(|&p| self.fixed(p).is_some()) {
+            for dir in 0..4 {
+                if let Some(q) = self.connected_neighbor(p, dir) {
+                    uf.unite(p, q);
+                }
+            }
+        }
+        uf
+    }
+
+    fn choose_dfs(&mut self, cells: &[(usize, usize)], pool: &mut [usize; 16], uf: &UnionFind, chosen: &mut Vec<u8>, nodes: &mut usize, rng: &mut SolverRng) -> bool {
+        let n = self.state.n;
+        let k = chosen.len();
+        if k == cells.len() {
+            return self.is_extendable_with(pool, uf);
+        }
+        *nodes += 1;
+        if *nodes > CHOICE_NODE_LIMIT {
+            return false;
+        }
+        let cell = cells[k];
+        let p = cell.0 * n + cell.1;
+        // 近くにあるタイルから試す．同じくらいの距離なら乱択する
+        let mut candidates: Vec<(f64, u8)> = (1..16u8)
+            .filter(|&tile| pool[tile as usize] > 0 && self.violations(p, tile) == 0)
+            .map(|tile| (self.nearest(tile, cell) as f64 + rng.gen::<f64>() * 2.0, tile))
+            .collect();
+        candidates.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for (_, tile) in candidates {
+            let mut next_uf = uf.clone();
+            if !self.join(p, tile, &mut next_uf) {
+                continue;
+            }
+            self.set_fixed(p, Some(tile));
+            pool[tile as usize] -= 1;
+            chosen.push(tile);
+            if self.choose_dfs(cells, pool, &next_uf, chosen, nodes, rng) {
+                return true;
+            }
+            chosen.pop();
+            pool[tile as usize] += 1;
+            self.set_fixed(p, None);
+            if *nodes > CHOICE_NODE_LIMIT {
+                return false;
+            }
+        }
+        false
+    }
+
+    // 食い違う道の数とループの数が最も少ない種類を選ぶ
+    fn choose_greedy(&mut self, cell: (usize, usize), pool: &mut [usize; 16], uf: &mut UnionFind) {
+        let p = cell.0 * self.state.n + cell.1;
+        let tile = (1..16u8)
+            .filter(|&tile| pool[tile as usize] > 0)
+            .min_by_key(|&tile| (self.violations(p, tile) + if self.join(p, tile, &mut uf.clone()) { 0 } else { 2 }, self.nearest(tile, cell)))
+            .unwrap();
+        self.join(p, tile, uf);
+        self.set_fixed(p, Some(tile));
+        pool[tile as usize] -= 1;
+    }
+
+    // pからdirの向きに，種類を決めたマスと道がつながっていればそのマス
+    fn connected_neighbor(&self, p: usize, dir: usize) -> Option<usize> {
+        let n = self.state.n;
+        let (i, j) = self.state.board.neighbor(p / n, p % n, dir)?;
+        let q = i * n + j;
+        let (a, b) = (self.fixed(p)?, self.fixed(q)?);
+        (a & DIR_BITS[dir] != 0 && b & DIR_BITS[reverse_action(dir)] != 0).then_some(q)
+    }
+
+    // pにtileを置いたとき，盤面の外へ出る道と，種類を決めたマスとの道の食い違いの数
+    fn violations(&self, p: usize, tile: u8) -> usize {
+        let n = self.state.n;
+        (0..4)
+            .filter(|&dir| {
+                let has = tile & DIR_BITS[dir] != 0;
+                match self.state.board.neighbor(p / n, p % n, dir) {
+                    None => has,
+                    Some((i, j)) => self.fixed(i * n + j).is_some_and(|other| has != (other & DIR_BITS[reverse_action(dir)] != 0)),
+                }
+            })
+            .count()
+    }
+
+    // pにtileを置いてつながる連結成分をufで併合する．ループができるならfalse
+    fn join(&self, p: usize, tile: u8, uf: &mut UnionFind) -> bool {
+        let n = self.state.n;
+        let mut ok = true;
+        for dir in 0..4 {
+            if tile & DIR_BITS[dir] == 0 {
+                continue;
+            }
+            if let Some((i, j)) = self.state.board.neighbor(p / n, p % n, dir) {
+                let q = i * n + j;
+                if self.fixed(q).is_some_and(|other| other & DIR_BITS[reverse_action(dir)] != 0) && !uf.unite(p, q) {
+                    ok = false;
+                }
+            }
+        }
+        ok
+    }
+
+    // 種類を決めたタイルの連結成分がどれも，まだ決めていないマスへ伸びる道を持ち，
+    // 固定していないタイルでその道を受けられるか
+    pub fn is_extendable(&self) -> bool {
+        self.is_extendable_with(&self.pool(), &self.components())
+    }
+
+    // poolは固定していないタイルから，種類を決めたマスに置くぶんを除いた枚数
+    fn is_extendable_with(&self, pool: &[usize; 16], uf: &UnionFind) -> bool {
+        let n = self.state.n;
+        let mut uf = uf.clone();
+        let mut open = vec![false; n * n];
+        let mut members = vec![false; n * n];
+        // 決めていないマスごとの，道が来ている向きと来ていない向き
+        let mut need_with = [0; 4];
+        let mut need_without = [0; 4];
+        for p in 0..n * n {
+            let tile = match self.fixed(p) {
+                Some(tile) => tile,
+                None => continue,
+            };
+            let root = uf.find(p);
+            members[root] = true;
+            for dir in 0..4 {
+                if let Some((i, j)) = self.state.board.neighbor(p / n, p % n, dir) {
+                    if self.fixed(i * n + j).is_none() {
+                        if tile & DIR_BITS[dir] != 0 {
+                            open[root] = true;
+                            need_with[reverse_action(dir)] += 1;
+                        } else {
+                            need_without[reverse_action(dir)] += 1;
+                        }
+                    }
+                }
+            }
+        }
+        if (0..n * n).any(|root| members[root] && !open[root]) {
+            return false;
+        }
+        // 空きマスが1つあるので，道のないほうは1枚少なくてもよい
+        (0..4).all(|dir| {
+            let with: usize = (1..16).filter(|&tile| tile & DIR_BITS[dir] as usize != 0).map(|tile| pool[tile]).sum();
+            let without: usize = (1..16).filter(|&tile| tile & DIR_BITS[dir] as usize == 0).map(|tile| pool[tile]).sum();
+            with >= need_with[dir] && without + 1 >= need_without[dir]
+        })
+    }
+
+    // 固定していないマスにあるtileの種類のタイルのうち，cellに最も近いものまでの距離
+    fn nearest(&self, tile: u8, cell: (usize, usize)) -> usize {
+        let n = self.state.n;
+        (0..n * n)
+            .filter(|&p| !self.router.locked[p] && self.state.board.tiles[p] == tile)
+            .map(|p| (p / n).abs_diff(cell.0) + (p % n).abs_diff(cell.1))
+            .min()
+            .unwrap_or(usize::MAX)
+    }
+}
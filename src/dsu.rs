@@ -0,0 +1,48 @@
+// 素集合データ構造(union-find)
+// 経路圧縮とサイズによる併合で，ほぼ定数時間で併合と判定ができる
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> UnionFind {
+        UnionFind { parent: (0..n).collect(), size: vec![1; n] }
+    }
+
+    // xの属する集合の根
+    pub fn find(&mut self, x: usize) -> usize {
+        if self.parent[x] != x {
+            let root = self.find(self.parent[x]);
+            self.parent[x] = root;
+        }
+        self.parent[x]
+    }
+
+    // xとyの属する集合を併合する
+    // もともと同じ集合ならfalseを返す
+    pub fn unite(&mut self, x: usize, y: usize) -> bool {
+        let mut rx = self.find(x);
+        let mut ry = self.find(y);
+        if rx == ry {
+            return false;
+        }
+        if self.size[rx] < self.size[ry] {
+            std::mem::swap(&mut rx, &mut ry);
+        }
+        self.parent[ry] = rx;
+        self.size[rx] += self.size[ry];
+        true
+    }
+
+    pub fn same(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    // xの属する集合の大きさ
+    pub fn size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+}
@@ -0,0 +1,318 @@
+// 盤面の評価に使う関数たち
+// NxNのタイルを頂点，向かい合う道を辺とするグラフとして扱う
+use crate::board::{Board, MAX_N};
+use crate::dsu::UnionFind;
+use crate::tiles::{reverse_action, DIR_BITS};
+use std::ops::{Add, Sub};
+
+// 1つの連結成分の情報
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Component {
+    // 連結成分に含まれるタイルの数
+    pub tile_count: usize,
+    // 連結成分に含まれる辺の数
+    pub edge_count: usize,
+    // 辺の数がタイルの数以上ならループがある
+    pub has_cycle: bool,
+    // 連結成分を囲う最小の矩形
+    pub min_x: usize,
+    pub max_x: usize,
+    pub min_y: usize,
+    pub max_y: usize,
+}
+
+impl Component {
+    // 連結成分を囲う最小の矩形の面積
+    pub fn rectangle_area(&self) -> i32 {
+        ((self.max_x - self.min_x + 1) * (self.max_y - self.min_y + 1)) as i32
+    }
+}
+
+// 盤面全体の連結成分分解の結果
+#[derive(Clone, Debug)]
+pub struct ComponentAnalysis {
+    n: usize,
+    pub components: Vec<Component>,
+    // 各マスが属する連結成分の番号．空きマスはusize::MAX
+    pub component_of: [usize; MAX_N * MAX_N],
+}
+
+impl ComponentAnalysis {
+    // (i, j)のタイルが属する連結成分の番号
+    pub fn component_index(&self, i: usize, j: usize) -> Option<usize> {
+        match self.component_of[i * self.n + j] {
+            usize::MAX => None,
+            c => Some(c),
+        }
+    }
+
+    // (i, j)のタイルが属する連結成分
+    pub fn component_at(&self, i: usize, j: usize) -> Option<&Component> {
+        self.component_index(i, j).map(|c| &self.components[c])
+    }
+
+    // ループを持たない連結成分のうち，最大のものの番号
+    pub fn largest_tree(&self) -> Option<usize> {
+        (0..self.components.len())
+            .filter(|&c| !self.components[c].has_cycle)
+            .max_by_key(|&c| (self.components[c].tile_count, std::cmp::Reverse(c)))
+    }
+
+    // 盤面全体で最大の木の大きさ
+    pub fn max_tree_size(&self) -> i32 {
+        self.largest_tree().map_or(0, |c| self.components[c].tile_count as i32)
+    }
+}
+
+// union-findで盤面全体を一度に連結成分に分け，それぞれのタイル数･辺数･ループの有無を求める
+// 連結成分の番号は，行優先で最初に現れるタイルの順につける
+pub fn analyze_components(board: &Board) -> ComponentAnalysis {
+    let n = board.n;
+    let mut uf = UnionFind::new(n * n);
+    // 下と右の辺だけを見れば，すべての辺を1回ずつ数えられる
+    let mut edges = vec![];
+    for i in 0..n {
+        for j in 0..n {
+            for dir in 0..2 {
+                if board.is_connected(i, j, dir) {
+                    let (x, y) = board.neighbor(i, j, dir).unwrap();
+                    uf.unite(i * n + j, x * n + y);
+                    edges.push(i * n + j);
+                }
+            }
+        }
+    }
+
+    let mut components: Vec<Component> = vec![];
+    let mut component_of = [usize::MAX; MAX_N * MAX_N];
+    let mut index_of_root = [usize::MAX; MAX_N * MAX_N];
+    for i in 0..n {
+        for j in 0..n {
+            if board.get(i, j) == 0 {
+                continue;
+            }
+            let root = uf.find(i * n + j);
+            if index_of_root[root] == usize::MAX {
+                index_of_root[root] = components.len();
+                components.push(Component { tile_count: 0, edge_count: 0, has_cycle: false, min_x: i, max_x: i, min_y: j, max_y: j });
+            }
+            let c = index_of_root[root];
+            component_of[i * n + j] = c;
+            let component = &mut components[c];
+            component.tile_count += 1;
+            component.min_x = component.min_x.min(i);
+            component.max_x = component.max_x.max(i);
+            component.min_y = component.min_y.min(j);
+            component.max_y = component.max_y.max(j);
+        }
+    }
+    for &v in &edges {
+        components[component_of[v]].edge_count += 1;
+    }
+    for component in components.iter_mut() {
+        component.has_cycle = component.edge_count >= component.tile_count;
+    }
+    ComponentAnalysis { n, components, component_of }
+}
+
+// 盤面上の1つの閉路
+// cellsは閉路を一周する順に並べたタイル，edgesは隣り合うタイルの組
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Cycle {
+    pub cells: Vec<(usize, usize)>,
+    pub edges: Vec<((usize, usize), (usize, usize))>,
+}
+
+impl Cycle {
+    // 閉路の長さ(辺の数)
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+}
+
+// 盤面上の閉路の基底(独立な閉路すべて)を求める
+// BFSで全域森を作り，森に使われなかった辺1本ごとに，その辺と森の上の道でできる閉路を1つ返す
+// 閉路の数は(辺の数) - (タイルの数) + (連結成分の数)になる
+pub fn find_cycles(board: &Board) -> Vec<Cycle> {
+    cycles_from(board, 0..board.n * board.n)
+}
+
+// rootを含む連結成分の閉路の基底の長さ(辺の数)の合計
+// rootを連結成分で番号の最も小さいマスにすれば，find_cyclesで数えた長さと一致する
+pub fn component_cycle_length(board: &Board, root: usize) -> usize {
+    cycles_from(board, std::iter::once(root)).iter().map(Cycle::len).sum()
+}
+
+// rootsから順にBFSで全域森を作り，そこに含まれる閉路の基底を求める
+fn cycles_from(board: &Board, roots: impl Iterator<Item = usize>) -> Vec<Cycle> {
+    let n = board.n;
+    let mut parent = [usize::MAX; MAX_N * MAX_N];
+    let mut depth = [usize::MAX; MAX_N * MAX_N];
+    // 全域森を作る
+    for root in roots {
+        if board.tiles[root] == 0 || depth[root] != usize::MAX {
+            continue;
+        }
+        depth[root] = 0;
+        let mut queue = vec![root];
+        let mut head = 0;
+        while head < queue.len() {
+            let v = queue[head];
+            head += 1;
+            for dir in 0..4 {
+                if !board.is_connected(v / n, v % n, dir) {
+                    continue;
+                }
+                let (x, y) = board.neighbor(v / n, v % n, dir).unwrap();
+                let u = x * n + y;
+                if depth[u] == usize::MAX {
+                    depth[u] = depth[v] + 1;
+                    parent[u] = v;
+                    queue.push(u);
+                }
+            }
+        }
+    }
+
+    // 森に使われなかった辺ごとに閉路を作る
+    let mut cycles = vec![];
+    for v in (0..n * n).filter(|&v| depth[v] != usize::MAX) {
+        // 下と右の辺だけを見れば，すべての辺を1回ずつ数えられる
+        for dir in 0..2 {
+            if !board.is_connected(v / n, v % n, dir) {
+                continue;
+            }
+            let (x, y) = board.neighbor(v / n, v % n, dir).unwrap();
+            let u = x * n + y;
+            if parent[u] == v || parent[v] == u {
+                continue;
+            }
+            // vとuから根の方向にたどり，合流するまでの道をつなげる
+            let (mut a, mut b) = (v, u);
+            let mut path_a = vec![a];
+            let mut path_b = vec![b];
+            while depth[a] > depth[b] {
+                a = parent[a];
+                path_a.push(a);
+            }
+            while depth[b] > depth[a] {
+                b = parent[b];
+                path_b.push(b);
+            }
+            while a != b {
+                a = parent[a];
+                b = parent[b];
+                path_a.push(a);
+                path_b.push(b);
+            }
+            // 合流点は2回入っているので片方を除く
+            path_b.pop();
+            path_b.reverse();
+            path_a.extend(path_b);
+            let cells: Vec<(usize, usize)> = path_a.iter().map(|&c| (c / n, c % n)).collect();
+            let edges = (0..cells.len()).map(|k| (cells[k], cells[(k + 1) % cells.len()])).collect();
+            cycles.push(Cycle { cells, edges });
+        }
+    }
+    cycles
+}
+
+// 描画するときの各タイルの分類
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileClass {
+    Empty,
+    Normal,
+    // 盤面で最大の木に含まれる
+    LargestTree,
+    // ループを持つ連結成分に含まれる
+    Loop,
+}
+
+// 盤面全体を連結成分に分け，各タイルの分類を決める
+pub fn classify_tiles(board: &Board) -> Vec<Vec<TileClass>> {
+    let n = board.n;
+    let analysis = analyze_components(board);
+    let largest_tree = analysis.largest_tree();
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| match analysis.component_index(i, j) {
+                    None => TileClass::Empty,
+                    Some(c) if analysis.components[c].has_cycle => TileClass::Loop,
+                    Some(c) if Some(c) == largest_tree => TileClass::LargestTree,
+                    Some(_) => TileClass::Normal,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// つながる相手のいない道(半辺)の数
+// 例えば左端にある1番のタイルは盤面の外を向いているので，何をしても木に入れられない
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Dangling {
+    // 盤面の外を向いている道
+    pub border: i32,
+    // 隣のタイルに向かい合う道がない道(隣が空きマスの場合も含む)
+    pub mismatched: i32,
+}
+
+impl Dangling {
+    pub fn total(&self) -> i32 {
+        self.border + self.mismatched
+    }
+}
+
+impl Add for Dangling {
+    type Output = Dangling;
+    fn add(self, other: Dangling) -> Dangling {
+        Dangling { border: self.border + other.border, mismatched: self.mismatched + other.mismatched }
+    }
+}
+
+impl Sub for Dangling {
+    type Output = Dangling;
+    fn sub(self, other: Dangling) -> Dangling {
+        Dangling { border: self.border - other.border, mismatched: self.mismatched - other.mismatched }
+    }
+}
+
+// (i, j)のタイルから出ている道のうち，つながる相手のいないもの
+pub fn dangling_at(board: &Board, i: usize, j: usize) -> Dangling {
+    let tile = board.get(i, j);
+    let mut dangling = Dangling::default();
+    for dir in 0..4 {
+        if tile & DIR_BITS[dir] == 0 {
+            continue;
+        }
+        match board.neighbor(i, j, dir) {
+            None => dangling.border += 1,
+            Some((x, y)) if board.get(x, y) & DIR_BITS[reverse_action(dir)] == 0 => dangling.mismatched += 1,
+            Some(_) => {}
+        }
+    }
+    dangling
+}
+
+// 盤面全体のつながる相手のいない道の数
+pub fn count_dangling(board: &Board) -> Dangling {
+    let n = board.n;
+    (0..n * n).map(|p| dangling_at(board, p / n, p % n)).fold(Dangling::default(), |a, b| a + b)
+}
+
+// aとbのタイルを入れ替えたときに数が変わりうるマス(a, bとその隣)についての合計
+// 入れ替えの前後でこれを引いて足せば，盤面全体の数を差分で更新できる
+pub fn dangling_around(board: &Board, a: (usize, usize), b: (usize, usize)) -> Dangling {
+    let mut cells = Vec::with_capacity(10);
+    for &(i, j) in &[a, b] {
+        cells.push((i, j));
+        cells.extend((0..4).filter_map(|dir| board.neighbor(i, j, dir)));
+    }
+    cells.sort_unstable();
+    cells.dedup();
+    cells.iter().map(|&(i, j)| dangling_at(board, i, j)).fold(Dangling::default(), |a, b| a + b)
+}
@@ -0,0 +1,144 @@
+// 探索で状態の良し悪しを決める評価関数
+// 探索のコードを触らずに評価関数を差し替えられるよう，トレイトにしておく
+use crate::board::GameState;
+use std::fmt;
+
+// 評価関数の共通インターフェース
+// 大きいほど良い状態とする
+pub trait Evaluator {
+    fn evaluate(&self, state: &GameState) -> f64;
+}
+
+// 20220602~20220605に提出していた評価関数
+// 1. 木が大きいほどよい
+// 2. 全域木が完成していれば，手番が少ないほどよい
+// 3. 例えば1番のタイルは左端にない方がよい ← 実装鬼
+// 4. 序盤のループや木の小ささは許容する
+pub struct LegacyEvaluator;
+
+impl Evaluator for LegacyEvaluator {
+    fn evaluate(&self, state: &GameState) -> f64 {
+        let loop_penalty = if state.has_loop {
+            0.01
+        } else {
+            0.0
+        };
+        // 木の大きさと木を囲う最小の矩形の大きさの差が小さいほどいい
+        // 当時はi32に丸めていたので，同点の扱いを変えないよう丸める
+        (state.tree_size as f64 - loop_penalty * state.turn as f64 + 0.001 * state.rectangle_area as f64 * state.turn as f64).round()
+    }
+}
+
+// 評価に使う特徴量
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Features {
+    // 動かしたタイルを含む木の大きさ
+    pub tree_size: f64,
+    // 盤面全体で最大の木の大きさ
+    pub max_tree_size: f64,
+    // 盤面全体の独立な閉路の数
+    pub cycle_count: f64,
+    // 盤面全体の閉路の基底の長さの合計．長い閉路ほど崩すのに手数がかかる
+    pub cycle_length: f64,
+    // 動かしたタイルを含む木が，それを囲う最小の矩形をどれだけ埋めているか(0~1)
+    pub fill_ratio: f64,
+    // 連結成分の数
+    pub component_count: f64,
+    // つながる相手のいない道の数
+    pub dangling: f64,
+    // 手番
+    pub turn: f64,
+}
+
+impl Features {
+    pub fn from_state(state: &GameState) -> Features {
+        Features {
+            tree_size: state.tree_size as f64,
+            max_tree_size: state.max_tree_size as f64,
+            cycle_count: state.components.cycle_count() as f64,
+            cycle_length: state.components.cycle_length() as f64,
+            fill_ratio: if state.rectangle_area > 0 { state.tree_size as f64 / state.rectangle_area as f64 } else { 0.0 },
+            component_count: state.components.component_count() as f64,
+            dangling: state.dangling.total() as f64,
+            turn: state.turn as f64,
+        }
+    }
+}
+
+// 各特徴量の重み
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Weights {
+    pub tree_size: f64,
+    pub max_tree_size: f64,
+    pub cycle_count: f64,
+    pub cycle_length: f64,
+    pub fill_ratio: f64,
+    pub component_count: f64,
+    pub dangling: f64,
+    pub turn: f64,
+}
+
+impl Default for Weights {
+    // 木を大きくすることを第一に，閉路(長いものほど)と細切れの連結成分，行き場のない道を少しだけ嫌う
+    fn default() -> Weights {
+        Weights { tree_size: 1.0, max_tree_size: 0.5, cycle_count: -0.5, cycle_length: -0.05, fill_ratio: 1.0, component_count: -0.1, dangling: -0.1, turn: 0.0 }
+    }
+}
+
+// 重みの設定の書き間違い
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightsParseError(String);
+
+impl fmt::Display for WeightsParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid weights: {}", self.0)
+    }
+}
+
+impl std::error::Error for WeightsParseError {}
+
+impl Weights {
+    // "tree_size=1,cycle_count=-0.5"のような文字列から重みを読む
+    // 書かれていない特徴量は既定値のまま
+    pub fn parse(s: &str) -> Result<Weights, WeightsParseError> {
+        let mut weights = Weights::default();
+        for item in s.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let mut kv = item.splitn(2, '=');
+            let key = kv.next().unwrap().trim();
+            let value: f64 = match kv.next().map(|v| v.trim().parse()) {
+                Some(Ok(value)) => value,
+                _ => return Err(WeightsParseError(format!("{:?} is not key=value", item))),
+            };
+            match key {
+                "tree_size" => weights.tree_size = value,
+                "max_tree_size" => weights.max_tree_size = value,
+                "cycle_count" => weights.cycle_count = value,
+                "cycle_length" => weights.cycle_length = value,
+                "fill_ratio" => weights.fill_ratio = value,
+                "component_count" => weights.component_count = value,
+                "dangling" => weights.dangling = value,
+                "turn" => weights.turn = value,
+                _ => return Err(WeightsParseError(format!("unknown feature {:?}", key))),
+            }
+        }
+        Ok(weights)
+    }
+}
+
+// 特徴量の重み付き和で評価する
+pub struct WeightedEvaluator {
+    pub weights: Weights,
+}
+
+impl WeightedEvaluator {
+    pub fn score(&self, f: &Features) -> f64 {
+        let w = &self.weights;
+        w.tree_size * f.tree_size + w.max_tree_size * f.max_tree_size + w.cycle_count * f.cycle_count + w.cycle_length * f.cycle_length + w.fill_ratio * f.fill_ratio + w.component_count * f.component_count + w.dangling * f.dangling + w.turn * f.turn
+    }
+}
+
+impl Evaluator for WeightedEvaluator {
+    fn evaluate(&self, state: &GameState) -> f64 {
+        self.score(&Features::from_state(state))
+    }
+}
@@ -0,0 +1,61 @@
+// 公式と同じ手順でテストケースを生成する
+// 1. 右下を空きマスとして，残りN^2-1マスのランダムな全域木を作る
+// 2. 全域木の辺からタイルを決める
+// 3. 空きマスをランダムにT=2N^3回動かしてシャッフルする
+use crate::board::{get_legal_actions, Input};
+use crate::dsu::UnionFind;
+use crate::tiles::{tile_to_char, ACTION_CHARS, DIR_BITS, DX, DY};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+// seedとNからテストケースを作る
+// Nを指定しなければseedから6~10の間で決める
+pub fn gen(seed: u64, n: Option<usize>) -> Input {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let n = n.unwrap_or_else(|| rng.gen_range(6, 11));
+    let t = 2 * n * n * n;
+
+    // 右下を除いたマスの間の辺をシャッフルし，クラスカル法で全域木を作る
+    let empty = n * n - 1;
+    let mut edges = vec![];
+    for i in 0..n {
+        for j in 0..n {
+            let v = i * n + j;
+            if i + 1 < n && v + n != empty {
+                edges.push((v, v + n, 0));
+            }
+            if j + 1 < n && v + 1 != empty {
+                edges.push((v, v + 1, 1));
+            }
+        }
+    }
+    edges.shuffle(&mut rng);
+    let mut uf = UnionFind::new(n * n);
+    let mut tiles = vec![0_u8; n * n];
+    for &(u, v, dir) in &edges {
+        if !uf.unite(u, v) {
+            continue;
+        }
+        // uから見てdir方向(下か右)，vから見てその逆方向に道を作る
+        tiles[u] |= DIR_BITS[dir];
+        tiles[v] |= DIR_BITS[dir + 2];
+    }
+    let mut board: Vec<Vec<char>> = (0..n)
+        .map(|i| (0..n).map(|j| tile_to_char(tiles[i * n + j])).collect())
+        .collect();
+
+    // 直前の手を相殺しないようにしながら空きマスをT回動かす
+    let mut empty = (n - 1, n - 1);
+    let mut operation_list = vec![];
+    for _ in 0..t {
+        let action = *get_legal_actions(&operation_list, empty, n).choose(&mut rng).unwrap();
+        let next = ((empty.0 as i32 + DX[action]) as usize, (empty.1 as i32 + DY[action]) as usize);
+        board[empty.0][empty.1] = board[next.0][next.1];
+        board[next.0][next.1] = '0';
+        empty = next;
+        operation_list.push(ACTION_CHARS[action]);
+    }
+    Input { n, t, board }
+}
+
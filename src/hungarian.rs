@@ -0,0 +1,64 @@
+// ハンガリアン法(割当問題)
+// k x kのコスト行列に対し，コストの合計が最小になる行と列の1対1の対応をO(k^3)で求める
+
+// cost[i][j]は行iを列jに割り当てるコスト
+// 行iを割り当てる列を返す
+pub fn hungarian(cost: &[Vec<i64>]) -> Vec<usize> {
+    let k = cost.len();
+    // 1始まりで，0番目の列を番兵に使う
+    let mut u = vec![0; k + 1];
+    let mut v = vec![0; k + 1];
+    // 列jに割り当てた行(1始まり)
+    let mut row_of = vec![0; k + 1];
+    let mut way = vec![0; k + 1];
+    for i in 1..=k {
+        row_of[0] = i;
+        let mut j0 = 0;
+        let mut min_v = vec![i64::MAX; k + 1];
+        let mut used = vec![false; k + 1];
+        // 行iを加えたときの増加路を，ポテンシャルを更新しながら探す
+        loop {
+            used[j0] = true;
+            let i0 = row_of[j0];
+            let mut delta = i64::MAX;
+            let mut j1 = 0;
+            for j in 1..=k {
+                if used[j] {
+                    continue;
+                }
+                let reduced = cost[i0 - 1][j - 1] - u[i0] - v[j];
+                if reduced < min_v[j] {
+                    min_v[j] = reduced;
+                    way[j] = j0;
+                }
+                if min_v[j] < delta {
+                    delta = min_v[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=k {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_v[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if row_of[j0] == 0 {
+                break;
+            }
+        }
+        // 増加路をたどって割り当てを入れ替える
+        while j0 != 0 {
+            let j1 = way[j0];
+            row_of[j0] = row_of[j1];
+            j0 = j1;
+        }
+    }
+    let mut column_of = vec![0; k];
+    for j in 1..=k {
+        column_of[row_of[j] - 1] = j - 1;
+    }
+    column_of
+}
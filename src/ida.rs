@@ -0,0 +1,371 @@
+// 目標配置が決まっているときの最短手順探索(IDA*)
+// 下界には，種類ごとに移動距離の合計が最小になるよう対応させたときのマンハッタン距離の合計と，
+// 1枚しかない種類のタイルどうしの linear conflict を使う
+// 小さい盤面全体にも，大きい盤面の最後の一部(movableなマスだけ)にも使える
+// pattern databaseを渡せば，その値とマンハッタン距離の下界の大きいほうを使う
+use crate::board::Board;
+use crate::hungarian::hungarian;
+use crate::parity::Assignment;
+use crate::pdb::PatternDatabase;
+use crate::tiles::{reverse_action, ACTION_CHARS};
+use std::fmt;
+use std::time::Instant;
+
+// 時刻を確認する間隔(展開したノードの数)
+const TIME_CHECK_INTERVAL: usize = 1024;
+
+// 探索を打ち切った理由
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdaError {
+    // 展開したノードの数がnode_limitを超えた
+    NodeLimit,
+    // deadlineを過ぎた
+    TimeLimit,
+    // 動かせるマスのタイルの種類ごとの枚数が目標配置と違うか，置換の偶奇が合わない
+    Unsolvable,
+}
+
+impl fmt::Display for IdaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdaError::NodeLimit => write!(f, "node limit exceeded"),
+            IdaError::TimeLimit => write!(f, "time limit exceeded"),
+            IdaError::Unsolvable => write!(f, "the target is not reachable"),
+        }
+    }
+}
+
+impl std::error::Error for IdaError {}
+
+// IDA*の設定
+pub struct IdaStar {
+    // 展開するノードの数の上限
+    pub node_limit: usize,
+    // この時刻を過ぎたら打ち切る
+    pub deadline: Instant,
+}
+
+impl IdaStar {
+    // 盤面をtargetの配置まで動かす最短の操作列を返す
+    // movableがfalseのマスは動かさない．そこはすでにtargetと同じになっていること
+    pub fn solve(&self, board: &Board, empty: (usize, usize), target: &Board, movable: &[bool]) -> Result<Vec<char>, IdaError> {
+        self.solve_inner(board, empty, target, movable, None)
+    }
+
+    // pattern databaseの目標配置まで，その値も下界に使って動かす
+    pub fn solve_with_database(&self, board: &Board, empty: (usize, usize), database: &PatternDatabase) -> Result<Vec<char>, IdaError> {
+        self.solve_inner(board, empty, &database.target, &database.movable, Some(database))
+    }
+
+    fn solve_inner(&self, board: &Board, empty: (usize, usize), target: &Board, movable: &[bool], database: Option<&PatternDatabase>) -> Result<Vec<char>, IdaError> {
+        let n = board.n;
+        let cells: Vec<usize> = (0..n * n).filter(|&p| movable[p]).collect();
+        let count = |board: &Board| {
+            let mut counts = [0; 16];
+            for &p in &cells {
+                counts[board.tiles[p] as usize] += 1;
+            }
+            counts
+        };
+        if count(board) != count(target) {
+            return Err(IdaError::Unsolvable);
+        }
+        if !is_solvable(board, target, movable, &cells) {
+            return Err(IdaError::Unsolvable);
+        }
+
+        let mut search = Search::new(board, empty, target, movable, database);
+        search.node_limit = self.node_limit;
+        search.deadline = self.deadline;
+        let mut threshold = search.heuristic();
+        loop {
+            match search.dfs(0, threshold, None)? {
+                None => return Ok(search.path.iter().map(|&action| ACTION_CHARS[action]).collect()),
+                // どこにも動かせない
+                Some(i64::MAX) => return Err(IdaError::Unsolvable),
+                Some(next) => threshold = next,
+            }
+        }
+    }
+}
+
+// IDA*が使う下界(最短手数以下の値)
+// databaseがなければマンハッタン距離とlinear conflictの和，あればそれとpattern databaseの値の大きいほう
+// 動かせるマスのタイルの種類ごとの枚数がtargetと同じであること
+pub fn lower_bound(board: &Board, target: &Board, movable: &[bool], database: Option<&PatternDatabase>) -> i64 {
+    let n = board.n;
+    let empty = (0..n * n).find(|&p| movable[p] && board.tiles[p] == 0).unwrap();
+    Search::new(board, (empty / n, empty % n), target, movable, database).heuristic()
+}
+
+// 動かせるマスの中で，targetの配置へ動かせるか
+// 移動距離が最小の対応について，置換の偶奇と空きマスの移動距離の偶奇を比べる(parity.rsと同じ判定)
+// 偶奇が合わなくても，同じ種類のタイルが2枚あれば行き先を入れ替えて直せる
+// ただし入れ替えられるのは，領域に関節点がなく，ただの閉路でもないときだけ
+// 廊下や木の形の領域ではタイルの並び順が変えられず，閉路ではタイルを回すことしかできない
+// その場合の判定は必要条件だけなので，たどり着けない配置でもtrueになることがある
+fn is_solvable(board: &Board, target: &Board, movable: &[bool], cells: &[usize]) -> bool {
+    let n = board.n;
+    let assignment = match Assignment::min_cost_within(board, target, movable) {
+        Some(assignment) => assignment,
+        None => return false,
+    };
+    let empty = cells.iter().copied().find(|&p| board.tiles[p] == 0).unwrap();
+    let target_empty = cells.iter().copied().find(|&p| target.tiles[p] == 0).unwrap();
+    if assignment.is_reachable((empty / n, empty % n), (target_empty / n, target_empty % n)) {
+        return true;
+    }
+    let mut seen = [false; 16];
+    let has_identical = cells.iter().any(|&p| {
+        let tile = board.tiles[p] as usize;
+        let identical = tile != 0 && seen[tile];
+        seen[tile] = true;
+        identical
+    });
+    has_identical && can_swap_identical(n, movable, cells)
+}
+
+// 領域の中で，同じ種類のタイル2枚の行き先を入れ替えられるか
+// 関節点がなく(2点連結)，次数3以上のマスがある(ただの閉路でない)ときは，置換の偶奇だけでたどり着けるかが決まる
+fn can_swap_identical(n: usize, movable: &[bool], cells: &[usize]) -> bool {
+    let neighbors = |p: usize| {
+        let (i, j) = (p / n, p % n);
+        let mut result = vec![];
+        if i > 0 {
+            result.push(p - n);
+        }
+        if i + 1 < n {
+            result.push(p + n);
+        }
+        if j > 0 {
+            result.push(p - 1);
+        }
+        if j + 1 < n {
+            result.push(p + 1);
+        }
+        result.into_iter().filter(|&q| movable[q]).collect::<Vec<usize>>()
+    };
+    if !cells.iter().any(|&p| neighbors(p).len() >= 3) {
+        return false;
+    }
+    // DFSの訪問順(order)と，後退辺で戻れる最小の訪問順(low)で関節点を探す
+    let mut order = vec![usize::MAX; n * n];
+    let mut low = vec![0; n * n];
+    let mut counter = 0;
+    let root = cells[0];
+    order[root] = 0;
+    low[root] = 0;
+    counter += 1;
+    let mut root_children = 0;
+    // (マス, 親, 次に見る隣のマスの番号)
+    let mut stack = vec![(root, usize::MAX, 0)];
+    while let Some(&mut (p, parent, ref mut next)) = stack.last_mut() {
+        let adjacent = neighbors(p);
+        if *next < adjacent.len() {
+            let q = adjacent[*next];
+            *next += 1;
+            if order[q] == usize::MAX {
+                order[q] = counter;
+                low[q] = counter;
+                counter += 1;
+                if p == root {
+                    root_children += 1;
+                }
+                stack.push((q, p, 0));
+            } else if q != parent {
+                low[p] = low[p].min(order[q]);
+            }
+        } else {
+            stack.pop();
+            if parent != usize::MAX {
+                low[parent] = low[parent].min(low[p]);
+                // 根以外のparentは，子pの部分木がparentより上へ戻れなければ関節点
+                if parent != root && low[p] >= order[parent] {
+                    return false;
+                }
+            }
+        }
+    }
+    // 根は子が2つ以上なら関節点．領域が連結でなくても入れ替えられない
+    root_children < 2 && cells.iter().all(|&p| order[p] != usize::MAX)
+}
+
+// 探索中の状態
+struct Search<'a> {
+    n: usize,
+    board: Board,
+    empty: (usize, usize),
+    movable: Vec<bool>,
+    // 種類ごとの，移動距離の合計が最小になる対応でのマンハッタン距離の合計
+    type_cost: [i64; 16],
+    database: Option<&'a PatternDatabase>,
+    // pattern databaseのグループごとの下界
+    group_cost: Vec<i64>,
+    // 種類ごとの，いまタイルがあるマスと目標配置でのマス
+    positions: Vec<Vec<usize>>,
+    targets: Vec<Vec<usize>>,
+    path: Vec<usize>,
+    nodes: usize,
+    node_limit: usize,
+    deadline: Instant,
+}
+
+impl<'a> Search<'a> {
+    // 探索の上限は呼び出し側で入れる
+    fn new(board: &Board, empty: (usize, usize), target: &Board, movable: &[bool], database: Option<&'a PatternDatabase>) -> Search<'a> {
+        let n = board.n;
+        let mut positions = vec![vec![]; 16];
+        let mut targets = vec![vec![]; 16];
+        for p in (0..n * n).filter(|&p| movable[p]) {
+            positions[board.tiles[p] as usize].push(p);
+            targets[target.tiles[p] as usize].push(p);
+        }
+        let mut search = Search {
+            n,
+            board: *board,
+            empty,
+            movable: movable.to_vec(),
+            type_cost: [0; 16],
+            database,
+            group_cost: vec![],
+            positions,
+            targets,
+            path: vec![],
+            nodes: 0,
+            node_limit: 0,
+            deadline: Instant::now(),
+        };
+        for tile in 1..16 {
+            search.type_cost[tile] = search.cost_of_type(tile);
+        }
+        if let Some(database) = database {
+            search.group_cost = (0..database.group_count()).map(|g| database.group_bound(g, &search.positions)).collect();
+        }
+        search
+    }
+
+    fn distance(&self, p: usize, q: usize) -> i64 {
+        ((p / self.n) as i64 - (q / self.n) as i64).abs() + ((p % self.n) as i64 - (q % self.n) as i64).abs()
+    }
+
+    fn cost_of_type(&self, tile: usize) -> i64 {
+        let (positions, targets) = (&self.positions[tile], &self.targets[tile]);
+        match positions.len() {
+            0 => 0,
+            1 => self.distance(positions[0], targets[0]),
+            _ => {
+                let cost: Vec<Vec<i64>> = positions.iter().map(|&p| targets.iter().map(|&q| self.distance(p, q)).collect()).collect();
+                hungarian(&cost).into_iter().enumerate().map(|(i, j)| cost[i][j]).sum()
+            }
+        }
+    }
+
+    // linear conflict
+    // 1枚しかない種類のタイルは行き先が決まっているので，同じ行(列)に行き先があるのに順番が逆なら，
+    // どちらかが一度その行(列)から外れる必要があり，マンハッタン距離より2手以上多くかかる
+    // 行(列)ごとに，順番を保ったまま残せる最大の枚数(最長増加部分列)以外のタイルを数える
+    fn linear_conflict(&self) -> i64 {
+        let n = self.n;
+        let unique_goal = |p: usize| {
+            let tile = self.board.tiles[p] as usize;
+            if tile != 0 && self.movable[p] && self.targets[tile].len() == 1 {
+                Some(self.targets[tile][0])
+            } else {
+                None
+            }
+        };
+        let mut conflicts = 0;
+        for line in 0..n {
+            let row: Vec<usize> = (0..n).filter_map(|j| unique_goal(line * n + j)).filter(|&q| q / n == line).map(|q| q % n).collect();
+            let column: Vec<usize> = (0..n).filter_map(|i| unique_goal(i * n + line)).filter(|&q| q % n == line).map(|q| q / n).collect();
+            conflicts += row.len() - longest_increasing(&row) + column.len() - longest_increasing(&column);
+        }
+        2 * conflicts as i64
+    }
+
+    fn heuristic(&self) -> i64 {
+        let manhattan = self.type_cost.iter().sum::<i64>() + self.linear_conflict();
+        match self.database {
+            // どのグループにも入っていない種類は，マンハッタン距離を足す
+            Some(database) => {
+                let uncovered: i64 = (1..16).filter(|&tile| database.group_of(tile).is_none()).map(|tile| self.type_cost[tile]).sum();
+                manhattan.max(self.group_cost.iter().sum::<i64>() + uncovered)
+            }
+            None => manhattan,
+        }
+    }
+
+    fn update_group_cost(&mut self, tile: usize) {
+        if let Some(database) = self.database {
+            if let Some(g) = database.group_of(tile) {
+                self.group_cost[g] = database.group_bound(g, &self.positions);
+            }
+        }
+    }
+
+    // 閾値threshold以内で目標配置にたどり着けたらNone，そうでなければ閾値を超えた評価値の最小値を返す
+    fn dfs(&mut self, g: i64, threshold: i64, prev: Option<usize>) -> Result<Option<i64>, IdaError> {
+        let h = self.heuristic();
+        if g + h > threshold {
+            return Ok(Some(g + h));
+        }
+        // 下界が0なら，すべてのタイルが目標配置のマスにある
+        if h == 0 {
+            return Ok(None);
+        }
+        self.nodes += 1;
+        if self.nodes > self.node_limit {
+            return Err(IdaError::NodeLimit);
+        }
+        if self.nodes % TIME_CHECK_INTERVAL == 0 && Instant::now() >= self.deadline {
+            return Err(IdaError::TimeLimit);
+        }
+        let mut next_threshold = i64::MAX;
+        for action in 0..4 {
+            if prev == Some(reverse_action(action)) {
+                continue;
+            }
+            let next = match self.board.neighbor(self.empty.0, self.empty.1, action) {
+                Some(cell) if self.movable[cell.0 * self.n + cell.1] => cell,
+                _ => continue,
+            };
+            let tile = self.board.get(next.0, next.1) as usize;
+            let (from, to) = (next.0 * self.n + next.1, self.empty.0 * self.n + self.empty.1);
+            let k = self.positions[tile].iter().position(|&p| p == from).unwrap();
+            let empty = self.empty;
+            let old_cost = self.type_cost[tile];
+            self.empty = self.board.slide(empty, action);
+            self.positions[tile][k] = to;
+            self.type_cost[tile] = self.cost_of_type(tile);
+            self.update_group_cost(tile);
+            self.path.push(action);
+
+            let result = self.dfs(g + 1, threshold, Some(action));
+            if let Ok(None) = result {
+                return result;
+            }
+            self.path.pop();
+            self.type_cost[tile] = old_cost;
+            self.positions[tile][k] = from;
+            self.update_group_cost(tile);
+            self.empty = self.board.slide(self.empty, reverse_action(action));
+            if let Some(f) = result? {
+                next_threshold = next_threshold.min(f);
+            }
+        }
+        Ok(Some(next_threshold))
+    }
+}
+
+// 最長増加部分列の長さ
+fn longest_increasing(values: &[usize]) -> usize {
+    let mut tails: Vec<usize> = vec![];
+    for &v in values {
+        match tails.binary_search(&v) {
+            Ok(_) => {}
+            Err(i) if i == tails.len() => tails.push(v),
+            Err(i) => tails[i] = v,
+        }
+    }
+    tails.len()
+}
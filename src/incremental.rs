@@ -0,0 +1,230 @@
+// 1手ごとに連結成分の情報を差分で更新する
+// スライドで変わるのは2マスだけなので，その2マスに触れる連結成分だけを数え直す
+use crate::board::{Board, MAX_N};
+use crate::evaluation::{analyze_components, component_cycle_length, Component};
+use crate::tiles::{reverse_action, DIR_BITS};
+
+// どの連結成分にも属さない(空きマス)ことを示すラベル
+const NONE: u8 = u8::MAX;
+
+// 状態をたくさん持てるよう，連結成分の情報はu8に詰めて持つ
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Stat {
+    tile_count: u8,
+    edge_count: u8,
+    min_x: u8,
+    max_x: u8,
+    min_y: u8,
+    max_y: u8,
+    // 閉路の基底の長さの合計．閉路がなければ0
+    cycle_length: u16,
+}
+
+impl Stat {
+    fn to_component(self) -> Component {
+        Component {
+            tile_count: self.tile_count as usize,
+            edge_count: self.edge_count as usize,
+            has_cycle: self.edge_count >= self.tile_count,
+            min_x: self.min_x as usize,
+            max_x: self.max_x as usize,
+            min_y: self.min_y as usize,
+            max_y: self.max_y as usize,
+        }
+    }
+}
+
+// 差分更新できる連結成分の情報
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct IncrementalComponents {
+    n: usize,
+    // 各マスが属する連結成分のラベル
+    label: [u8; MAX_N * MAX_N],
+    // ラベルごとの連結成分の情報．使われていないラベルはNone
+    // 状態を複製するたびにヒープを確保しないよう，固定長の配列にして先頭のlen個だけ使う
+    // 連結成分はタイルの数(N^2未満)より多くならないので，ラベルもN^2個で足りる
+    stats: [Option<Stat>; MAX_N * MAX_N],
+    len: usize,
+    // 使われていないラベル(先頭のfree_len個)
+    free: [u8; MAX_N * MAX_N],
+    free_len: usize,
+}
+
+impl IncrementalComponents {
+    // 盤面全体から作る
+    pub fn new(board: &Board) -> IncrementalComponents {
+        let analysis = analyze_components(board);
+        let mut label = [NONE; MAX_N * MAX_N];
+        for (l, &c) in label.iter_mut().zip(analysis.component_of.iter()) {
+            if c != usize::MAX {
+                *l = c as u8;
+            }
+        }
+        // 連結成分の番号は行優先で最初に現れるタイルの順なので，最初に現れたマスを根にして閉路の長さを数える
+        let mut cycle_length = vec![0; analysis.components.len()];
+        let mut seen = vec![false; analysis.components.len()];
+        for p in 0..board.n * board.n {
+            let c = analysis.component_of[p];
+            if c != usize::MAX && !seen[c] {
+                seen[c] = true;
+                if analysis.components[c].has_cycle {
+                    cycle_length[c] = component_cycle_length(board, p) as u16;
+                }
+            }
+        }
+        let mut stats = [None; MAX_N * MAX_N];
+        for ((stat, c), &cycle_length) in stats.iter_mut().zip(analysis.components.iter()).zip(cycle_length.iter()) {
+            *stat = Some(Stat {
+                tile_count: c.tile_count as u8,
+                edge_count: c.edge_count as u8,
+                min_x: c.min_x as u8,
+                max_x: c.max_x as u8,
+                min_y: c.min_y as u8,
+                max_y: c.max_y as u8,
+                cycle_length,
+            });
+        }
+        IncrementalComponents { n: board.n, label, stats, len: analysis.components.len(), free: [0; MAX_N * MAX_N], free_len: 0 }
+    }
+
+    // (i, j)のタイルが属する連結成分
+    pub fn component_at(&self, i: usize, j: usize) -> Option<Component> {
+        match self.label[i * self.n + j] {
+            NONE => None,
+            l => self.stats[l as usize].map(Stat::to_component),
+        }
+    }
+
+    // (i, j)と(x, y)のタイルが同じ連結成分に属するか
+    pub fn same(&self, (i, j): (usize, usize), (x, y): (usize, usize)) -> bool {
+        let l = self.label[i * self.n + j];
+        l != NONE && l == self.label[x * self.n + y]
+    }
+
+    // 使われているラベルの連結成分の情報
+    fn used(&self) -> impl Iterator<Item = &Stat> {
+        self.stats[..self.len].iter().flatten()
+    }
+
+    // 今ある連結成分すべて
+    pub fn components(&self) -> Vec<Component> {
+        self.used().map(|s| s.to_component()).collect()
+    }
+
+    // 連結成分の数
+    pub fn component_count(&self) -> usize {
+        self.used().count()
+    }
+
+    // 独立な閉路の数((辺の数) - (タイルの数) + (連結成分の数))
+    pub fn cycle_count(&self) -> usize {
+        self.used().map(|s| s.edge_count as usize + 1 - s.tile_count as usize).sum()
+    }
+
+    // 盤面全体の閉路の基底の長さの合計(find_cyclesで求めた閉路の長さの合計と同じ)
+    pub fn cycle_length(&self) -> usize {
+        self.used().map(|s| s.cycle_length as usize).sum()
+    }
+
+    // 盤面全体で最大の木の大きさ
+    pub fn max_tree_size(&self) -> i32 {
+        self.used().filter(|s| s.edge_count < s.tile_count).map(|s| s.tile_count as i32).max().unwrap_or(0)
+    }
+
+    fn release(&mut self, l: u8) {
+        if self.stats[l as usize].take().is_some() {
+            self.free[self.free_len] = l;
+            self.free_len += 1;
+        }
+    }
+
+    fn allocate(&mut self, stat: Stat) -> u8 {
+        let l = if self.free_len > 0 {
+            self.free_len -= 1;
+            self.free[self.free_len]
+        } else {
+            self.len += 1;
+            (self.len - 1) as u8
+        };
+        self.stats[l as usize] = Some(stat);
+        l
+    }
+
+    // boardはスライド後の盤面，movedはタイルが動いた先(元の空きマス)，emptyは新しい空きマス
+    // 動いたタイルが元いた連結成分と，動いた先でつながる連結成分だけを数え直す
+    pub fn update_after_slide(&mut self, board: &Board, moved: (usize, usize), empty: (usize, usize)) {
+        let n = self.n;
+        let tile = board.get(moved.0, moved.1);
+        // 動く前にタイルとつながっていた隣のタイルを，元の連結成分の残りを探す始点にする
+        let mut seeds = vec![moved];
+        for dir in 0..4 {
+            if tile & DIR_BITS[dir] == 0 {
+                continue;
+            }
+            if let Some((x, y)) = board.neighbor(empty.0, empty.1, dir) {
+                if (x, y) != moved && board.get(x, y) & DIR_BITS[reverse_action(dir)] != 0 {
+                    seeds.push((x, y));
+                }
+            }
+        }
+        let old = self.label[empty.0 * n + empty.1];
+        self.label[empty.0 * n + empty.1] = NONE;
+        self.label[moved.0 * n + moved.1] = NONE;
+        if old != NONE {
+            self.release(old);
+        }
+
+        // 始点ごとにBFSし，新しい連結成分を集める
+        // 古いラベルを全部捨ててから振り直さないと，同じラベルを使い回してしまう
+        let mut visited = [false; MAX_N * MAX_N];
+        let mut new_components = vec![];
+        for &seed in &seeds {
+            if visited[seed.0 * n + seed.1] {
+                continue;
+            }
+            visited[seed.0 * n + seed.1] = true;
+            let mut queue = vec![seed];
+            let mut head = 0;
+            let mut degree_sum = 0;
+            let mut stat = Stat { tile_count: 0, edge_count: 0, min_x: seed.0 as u8, max_x: seed.0 as u8, min_y: seed.1 as u8, max_y: seed.1 as u8, cycle_length: 0 };
+            while head < queue.len() {
+                let (i, j) = queue[head];
+                head += 1;
+                // 合流する連結成分の古いラベルは捨てる
+                let l = self.label[i * n + j];
+                if l != NONE {
+                    self.release(l);
+                }
+                stat.tile_count += 1;
+                stat.min_x = stat.min_x.min(i as u8);
+                stat.max_x = stat.max_x.max(i as u8);
+                stat.min_y = stat.min_y.min(j as u8);
+                stat.max_y = stat.max_y.max(j as u8);
+                for dir in 0..4 {
+                    if !board.is_connected(i, j, dir) {
+                        continue;
+                    }
+                    degree_sum += 1;
+                    let (x, y) = board.neighbor(i, j, dir).unwrap();
+                    if !visited[x * n + y] {
+                        visited[x * n + y] = true;
+                        queue.push((x, y));
+                    }
+                }
+            }
+            stat.edge_count = (degree_sum / 2) as u8;
+            // 閉路があるときだけ，番号の最も小さいマスを根にして長さを数え直す
+            if stat.edge_count >= stat.tile_count {
+                let root = queue.iter().map(|&(i, j)| i * n + j).min().unwrap();
+                stat.cycle_length = component_cycle_length(board, root) as u16;
+            }
+            new_components.push((stat, queue));
+        }
+        for (stat, cells) in new_components {
+            let l = self.allocate(stat);
+            for &(i, j) in &cells {
+                self.label[i * n + j] = l;
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// AHC011の各解法で共通して使う部品をまとめたライブラリ
+// 各binはこのライブラリの上に探索の戦略だけを載せる薄いラッパーにする
+pub mod arrangement;
+pub mod bidirectional;
+pub mod board;
+pub mod config;
+pub mod constructive;
+pub mod dsu;
+pub mod evaluation;
+pub mod evaluator;
+pub mod gen;
+pub mod hungarian;
+pub mod ida;
+pub mod incremental;
+pub mod parity;
+pub mod pdb;
+pub mod puzzle;
+pub mod replay;
+pub mod scoring;
+pub mod search;
+pub mod svg;
+pub mod tiles;
+pub mod zobrist;
+
+use board::Input;
+
+// 探索で使う乱数生成機
+// 速さと再現性のため，シードを固定できるPCGを使う
+pub type SolverRng = rand_pcg::Pcg64Mcg;
+
+// 解法の共通インターフェース
+// 入力を受け取り，出力すべき操作列(UDLR)を返す
+pub trait Solver {
+    fn solve(&mut self, input: &Input) -> Vec<char>;
+}
@@ -0,0 +1,148 @@
+// 目標配置へスライドでたどり着けるかの判定
+// タイルに番号をつけて見ると，1回のスライドは空きマスと隣のタイルの互換なので，
+// 目標配置までの置換の偶奇と，空きマスの移動距離の偶奇が一致するときだけたどり着ける
+// 同じ種類のタイルが2枚あれば，その2枚の行き先を入れ替えることで置換の偶奇を変えられる
+use crate::board::Board;
+use crate::hungarian::hungarian;
+use std::fmt;
+
+// 目標配置のどのマスに，入力のどのマスのタイルを持っていくか
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Assignment {
+    pub n: usize,
+    // 目標配置のマス(行優先の番号)ごとの，入力でのマスの番号．空きマスは空きマスに対応させる
+    pub source_of: Vec<usize>,
+}
+
+impl Assignment {
+    // 種類ごとに，入力で行優先にk番目に現れるタイルを，目標配置でk番目に現れるマスへ持っていく
+    // 種類ごとの枚数が違えばNone
+    pub fn natural(board: &Board, target: &Board) -> Option<Assignment> {
+        let n = board.n;
+        let mut sources: Vec<Vec<usize>> = vec![vec![]; 16];
+        for p in (0..n * n).rev() {
+            sources[board.tiles[p] as usize].push(p);
+        }
+        let mut source_of = vec![0; n * n];
+        for (q, source) in source_of.iter_mut().enumerate() {
+            *source = sources[target.tiles[q] as usize].pop()?;
+        }
+        if sources.iter().any(|s| !s.is_empty()) {
+            return None;
+        }
+        Some(Assignment { n, source_of })
+    }
+
+    // 種類ごとに，移動距離(マンハッタン距離)の合計が最小になるよう入力のタイルと目標配置のマスを対応させる
+    // 種類ごとの枚数が違えばNone
+    pub fn min_cost(board: &Board, target: &Board) -> Option<Assignment> {
+        Assignment::min_cost_within(board, target, &vec![true; board.n * board.n])
+    }
+
+    // min_costと同じだが，movableがtrueのマスの中だけで対応させる．それ以外のマスは動かさないので自分自身に対応させる
+    pub fn min_cost_within(board: &Board, target: &Board, movable: &[bool]) -> Option<Assignment> {
+        let n = board.n;
+        let distance = |p: usize, q: usize| ((p / n) as i64 - (q / n) as i64).abs() + ((p % n) as i64 - (q % n) as i64).abs();
+        let mut source_of: Vec<usize> = (0..n * n).collect();
+        for tile in 0..16 {
+            let sources: Vec<usize> = (0..n * n).filter(|&p| movable[p] && board.tiles[p] == tile).collect();
+            let targets: Vec<usize> = (0..n * n).filter(|&q| movable[q] && target.tiles[q] == tile).collect();
+            if sources.len() != targets.len() {
+                return None;
+            }
+            let cost: Vec<Vec<i64>> = targets.iter().map(|&q| sources.iter().map(|&p| distance(p, q)).collect()).collect();
+            for (i, j) in hungarian(&cost).into_iter().enumerate() {
+                source_of[targets[i]] = sources[j];
+            }
+        }
+        Some(Assignment { n, source_of })
+    }
+
+    // 置換が奇置換か
+    // 巡回置換に分解し，長さkの巡回がk-1個の互換になることから数える
+    pub fn is_odd(&self) -> bool {
+        let mut visited = vec![false; self.source_of.len()];
+        let mut transpositions = 0;
+        for start in 0..self.source_of.len() {
+            let mut p = start;
+            let mut len = 0;
+            while !visited[p] {
+                visited[p] = true;
+                p = self.source_of[p];
+                len += 1;
+            }
+            transpositions += len.max(1) - 1;
+        }
+        transpositions % 2 == 1
+    }
+
+    // 空きマスをemptyからtarget_emptyへ動かしてこの対応を実現できるか
+    pub fn is_reachable(&self, empty: (usize, usize), target_empty: (usize, usize)) -> bool {
+        let distance = (empty.0 as i32 - target_empty.0 as i32).abs() + (empty.1 as i32 - target_empty.1 as i32).abs();
+        self.is_odd() == (distance % 2 == 1)
+    }
+
+    // 各タイルの移動距離(マンハッタン距離)の合計
+    pub fn total_distance(&self) -> usize {
+        (0..self.source_of.len()).map(|q| self.distance(self.source_of[q], q)).sum()
+    }
+
+    fn distance(&self, p: usize, q: usize) -> usize {
+        let (p, q) = ((p / self.n, p % self.n), (q / self.n, q % self.n));
+        ((p.0 as i32 - q.0 as i32).abs() + (p.1 as i32 - q.1 as i32).abs()) as usize
+    }
+}
+
+// 目標配置へたどり着けない理由
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParityError {
+    // 入力と目標配置で，タイルの種類ごとの枚数が違う
+    TileCountMismatch,
+    // 置換の偶奇が合わず，同じ種類のタイルがないので入れ替えて直すこともできない
+    NoIdenticalTiles,
+}
+
+impl fmt::Display for ParityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParityError::TileCountMismatch => write!(f, "the target does not use the same tiles as the input"),
+            ParityError::NoIdenticalTiles => write!(f, "parity mismatch and no identical tiles to swap"),
+        }
+    }
+}
+
+impl std::error::Error for ParityError {}
+
+// 偶奇が合わなければ，同じ種類のタイル2枚の行き先を入れ替えて直す
+// 入れ替える2枚は，移動距離の合計の増え方が最も小さいものを選ぶ
+pub fn repair(assignment: &Assignment, target: &Board, empty: (usize, usize), target_empty: (usize, usize)) -> Result<Assignment, ParityError> {
+    if assignment.is_reachable(empty, target_empty) {
+        return Ok(assignment.clone());
+    }
+    let cells = assignment.source_of.len();
+    let best = (0..cells)
+        .flat_map(|p| (p + 1..cells).map(move |q| (p, q)))
+        .filter(|&(p, q)| target.tiles[p] != 0 && target.tiles[p] == target.tiles[q])
+        .min_by_key(|&(p, q)| {
+            let (sp, sq) = (assignment.source_of[p], assignment.source_of[q]);
+            (assignment.distance(sq, p) + assignment.distance(sp, q)) as i32 - (assignment.distance(sp, p) + assignment.distance(sq, q)) as i32
+        });
+    let (p, q) = best.ok_or(ParityError::NoIdenticalTiles)?;
+    let mut repaired = assignment.clone();
+    repaired.source_of.swap(p, q);
+    Ok(repaired)
+}
+
+// 入力の盤面から目標配置へたどり着ける対応を返す
+// 種類ごとに順番に対応させ，偶奇が合わなければ同じ種類のタイルを入れ替えて直す
+pub fn check_reachability(board: &Board, empty: (usize, usize), target: &Board, target_empty: (usize, usize)) -> Result<Assignment, ParityError> {
+    let assignment = Assignment::natural(board, target).ok_or(ParityError::TileCountMismatch)?;
+    repair(&assignment, target, empty, target_empty)
+}
+
+// 移動距離の合計ができるだけ小さく，たどり着ける対応を返す
+// 種類ごとにハンガリアン法で対応させ，偶奇が合わなければ同じ種類のタイルを入れ替えて直す
+pub fn plan_assignment(board: &Board, empty: (usize, usize), target: &Board, target_empty: (usize, usize)) -> Result<Assignment, ParityError> {
+    let assignment = Assignment::min_cost(board, target).ok_or(ParityError::TileCountMismatch)?;
+    repair(&assignment, target, empty, target_empty)
+}
@@ -0,0 +1,234 @@
+// 目標配置の一部を解くときの disjoint pattern database
+// 動かせるマス(領域)のタイルの種類をいくつかのグループに分け，グループごとに，
+// グループの種類のタイルだけを区別し，ほかのタイルは区別しない盤面で，目標配置までにグループのタイルを動かす回数の最小値を表にする
+// 1回のスライドで動くタイルは1枚なので，グループごとの値の合計も下界になる
+// 同じ種類のタイルは区別しないので，どのタイルをどのマスへ持っていくかも込みの下界になる
+use crate::board::Board;
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Instant;
+
+// 1グループのタイルの枚数の上限
+const MAX_GROUP_TILES: usize = 8;
+// 領域のマスの数の上限
+const MAX_REGION_CELLS: usize = 64;
+// 時刻を確認する間隔(キューから取り出した状態の数)
+const TIME_CHECK_INTERVAL: usize = 1024;
+// 表にない配置の下界．目標配置にたどり着けない
+const UNREACHABLE: i64 = 1 << 40;
+
+// 1グループの表を作るときに使うメモリの上限(バイト)
+// 作る途中は空きマスの位置ごとに2バイト持つので，状態数はこの半分まで．メモリ制限1024MBに対して十分小さくしておく
+pub const MEMORY_BUDGET: usize = 64 << 20;
+
+// pattern databaseを作れない理由
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PdbError {
+    // 動かせるマスが多すぎる
+    RegionTooLarge,
+    // 目標配置の空きマスが動かせるマスにない
+    EmptyOutsideRegion,
+    // 表を作り終わる前にdeadlineを過ぎた
+    TimeLimit,
+}
+
+impl fmt::Display for PdbError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PdbError::RegionTooLarge => write!(f, "too many movable cells (at most {})", MAX_REGION_CELLS),
+            PdbError::EmptyOutsideRegion => write!(f, "the empty cell of the target is not movable"),
+            PdbError::TimeLimit => write!(f, "time limit exceeded while building the tables"),
+        }
+    }
+}
+
+impl std::error::Error for PdbError {}
+
+// 1グループ分の表
+struct PatternGroup {
+    // グループのタイルの種類と，領域の中の枚数
+    types: Vec<u8>,
+    counts: Vec<usize>,
+    // グループのタイルの位置(領域の中の番号)をcells進数で並べた番号ごとの，動かす回数の最小値
+    table: Vec<u16>,
+}
+
+pub struct PatternDatabase {
+    // 領域のマスの数
+    cells: usize,
+    pub target: Board,
+    pub target_empty: (usize, usize),
+    pub movable: Vec<bool>,
+    // 盤面のマスごとの領域の中の番号．領域の外はusize::MAX
+    index_of: Vec<usize>,
+    groups: Vec<PatternGroup>,
+    // タイルの種類ごとの，入っているグループ．多すぎてどのグループにも入れられなかった種類はNone
+    group_of: [Option<usize>; 16],
+}
+
+impl PatternDatabase {
+    // movableがtrueのマスの中でtargetの配置にするときの表を作る
+    // 1グループの表を作るときのメモリ(空きマスの位置も含めた状態の数 * 2バイト)がmemory_budget以下になるよう，
+    // 目標配置で先に現れる種類から順にまとめる．ふつうはMEMORY_BUDGETを渡す
+    pub fn build(target: &Board, target_empty: (usize, usize), movable: &[bool], memory_budget: usize, deadline: Instant) -> Result<PatternDatabase, PdbError> {
+        let n = target.n;
+        let max_states = memory_budget / std::mem::size_of::<u16>();
+        let region: Vec<usize> = (0..n * n).filter(|&p| movable[p]).collect();
+        if region.len() > MAX_REGION_CELLS {
+            return Err(PdbError::RegionTooLarge);
+        }
+        if !movable[target_empty.0 * n + target_empty.1] {
+            return Err(PdbError::EmptyOutsideRegion);
+        }
+        let mut index_of = vec![usize::MAX; n * n];
+        for (k, &p) in region.iter().enumerate() {
+            index_of[p] = k;
+        }
+        let neighbors: Vec<Vec<usize>> = region
+            .iter()
+            .map(|&p| (0..4).filter_map(|dir| target.neighbor(p / n, p % n, dir)).map(|(i, j)| index_of[i * n + j]).filter(|&k| k != usize::MAX).collect())
+            .collect();
+
+        let mut counts = [0; 16];
+        let mut order = vec![];
+        for &p in &region {
+            let tile = target.tiles[p] as usize;
+            if tile != 0 && counts[tile] == 0 {
+                order.push(tile);
+            }
+            counts[tile] += 1;
+        }
+        let fits = |types: &[usize]| {
+            let tiles: usize = types.iter().map(|&tile| counts[tile]).sum();
+            tiles <= MAX_GROUP_TILES && (region.len() as f64).powi(tiles as i32 + 1) <= max_states as f64
+        };
+        let mut type_groups: Vec<Vec<usize>> = vec![];
+        let mut current: Vec<usize> = vec![];
+        for tile in order {
+            if !fits(&[tile]) {
+                continue;
+            }
+            current.push(tile);
+            if !fits(&current) {
+                current.pop();
+                type_groups.push(current);
+                current = vec![tile];
+            }
+        }
+        if !current.is_empty() {
+            type_groups.push(current);
+        }
+
+        let mut group_of = [None; 16];
+        let empty = index_of[target_empty.0 * n + target_empty.1];
+        let mut groups = vec![];
+        for (g, types) in type_groups.into_iter().enumerate() {
+            let mut goal = vec![];
+            for &tile in &types {
+                group_of[tile] = Some(g);
+                goal.extend(region.iter().filter(|&&p| target.tiles[p] as usize == tile).map(|&p| index_of[p] as u8));
+            }
+            let counts: Vec<usize> = types.iter().map(|&tile| counts[tile]).collect();
+            let table = build_table(&goal, &counts, empty as u8, &neighbors, deadline).ok_or(PdbError::TimeLimit)?;
+            groups.push(PatternGroup { types: types.into_iter().map(|tile| tile as u8).collect(), counts, table });
+        }
+        Ok(PatternDatabase { cells: region.len(), target: *target, target_empty, movable: movable.to_vec(), index_of, groups, group_of })
+    }
+
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    // タイルの種類が入っているグループ
+    pub fn group_of(&self, tile: usize) -> Option<usize> {
+        self.group_of[tile]
+    }
+
+    // 表の大きさの合計
+    pub fn table_len(&self) -> usize {
+        self.groups.iter().map(|group| group.table.len()).sum()
+    }
+
+    // グループgのタイルを目標配置へ動かす回数の下界
+    // positionsは種類ごとの，いまタイルがあるマス(盤面のマスの番号)
+    pub fn group_bound(&self, g: usize, positions: &[Vec<usize>]) -> i64 {
+        let group = &self.groups[g];
+        let mut slots = vec![];
+        for &tile in &group.types {
+            slots.extend(positions[tile as usize].iter().map(|&p| self.index_of[p] as u8));
+        }
+        match group.table[index(&slots, &group.counts, self.cells)] {
+            u16::MAX => UNREACHABLE,
+            moves => moves as i64,
+        }
+    }
+
+    // 盤面の，どこかのグループに入っている種類のタイルを目標配置へ動かす回数の下界
+    pub fn lower_bound(&self, board: &Board) -> i64 {
+        let mut positions = vec![vec![]; 16];
+        for p in (0..board.n * board.n).filter(|&p| self.movable[p]) {
+            positions[board.tiles[p] as usize].push(p);
+        }
+        (0..self.groups.len()).map(|g| self.group_bound(g, &positions)).sum()
+    }
+}
+
+// 同じ種類のタイルは区別しないので，種類ごとに並べ替えてから，位置をcells進数で並べた番号にする
+fn index(slots: &[u8], counts: &[usize], cells: usize) -> usize {
+    let mut sorted = [0; MAX_GROUP_TILES];
+    sorted[..slots.len()].copy_from_slice(slots);
+    let mut start = 0;
+    for &c in counts {
+        sorted[start..start + c].sort_unstable();
+        start += c;
+    }
+    sorted[..slots.len()].iter().fold(0, |index, &p| index * cells + p as usize)
+}
+
+// 目標配置から空きマスを動かす0-1 BFSで，グループのタイルの配置ごとの動かす回数の最小値を求める
+// スライドは逆向きにたどってもスライドなので，目標配置から広げても同じ距離になる
+// グループ外のタイルとの入れ替えは0手，グループのタイルとの入れ替えは1手と数える
+// deadlineを過ぎたらNone
+fn build_table(goal: &[u8], counts: &[usize], empty: u8, neighbors: &[Vec<usize>], deadline: Instant) -> Option<Vec<u16>> {
+    let (tiles, cells) = (goal.len(), neighbors.len());
+    let size = cells.pow(tiles as u32);
+    // 状態の番号は，タイルの位置の番号 * cells + 空きマスの位置
+    let mut distance = vec![u16::MAX; size * cells];
+    let mut start = [0; MAX_GROUP_TILES];
+    start[..tiles].copy_from_slice(goal);
+    distance[index(goal, counts, cells) * cells + empty as usize] = 0;
+    let mut queue = VecDeque::new();
+    queue.push_back((start, empty, 0));
+    let mut popped = 0;
+    while let Some((slots, empty, d)) = queue.pop_front() {
+        popped += 1;
+        if popped % TIME_CHECK_INTERVAL == 0 && Instant::now() >= deadline {
+            return None;
+        }
+        if distance[index(&slots[..tiles], counts, cells) * cells + empty as usize] < d {
+            continue;
+        }
+        for &next in &neighbors[empty as usize] {
+            let mut next_slots = slots;
+            let cost = match slots[..tiles].iter().position(|&p| p as usize == next) {
+                Some(k) => {
+                    next_slots[k] = empty;
+                    1
+                }
+                None => 0,
+            };
+            let state = index(&next_slots[..tiles], counts, cells) * cells + next;
+            if distance[state] <= d + cost {
+                continue;
+            }
+            distance[state] = d + cost;
+            if cost == 0 {
+                queue.push_front((next_slots, next as u8, d));
+            } else {
+                queue.push_back((next_slots, next as u8, d + 1));
+            }
+        }
+    }
+    // 空きマスの位置は見ないので，空きマスの位置について最小値をとる
+    Some(distance.chunks(cells).map(|chunk| *chunk.iter().min().unwrap()).collect())
+}
@@ -0,0 +1,353 @@
+// スライドパズルとして，いまの盤面を目標配置まで動かす
+// 15パズルの定石どおり，上の行から1行ずつ，最後の2行は左の列から1列ずつ揃え，残った2x3だけを全探索する
+// 同じ種類のタイルは区別しないので，揃えるマスごとにその種類のタイルのうち近いものを持ってくる
+// どのタイルをどのマスに持っていくか(Assignment)を先に決めておけば，それに従って動かすこともできる
+use crate::board::{Board, MAX_N};
+use crate::parity::Assignment;
+use crate::tiles::{reverse_action, tile_to_char, ACTION_CHARS, DX, DY};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+// 目標配置まで動かせなかった理由
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RouteError {
+    // 目標配置にあるタイルが，揃え終わっていないマスに残っていない(タイルの種類ごとの枚数が違う)
+    MissingTile { tile: char, cell: (usize, usize) },
+    // 固定したマスに囲まれて，タイルか空きマスを動かせない
+    Stuck { cell: (usize, usize) },
+    // 最後の2x3をどう動かしても目標配置にならない(置換の偶奇が合わない)
+    Unsolvable,
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouteError::MissingTile { tile, cell } => write!(f, "no tile {} left for ({}, {})", tile, cell.0, cell.1),
+            RouteError::Stuck { cell } => write!(f, "cannot reach ({}, {}) without moving fixed tiles", cell.0, cell.1),
+            RouteError::Unsolvable => write!(f, "the last 2x3 block cannot reach the target (parity mismatch)"),
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+// 盤面をemptyからtarget_emptyへ動かし，targetと同じ種類のタイルの配置にする操作列を返す
+// 操作の数がTを超えるかどうかは見ないので，呼び出し側でinput.tと比べること
+pub fn route(board: &Board, empty: (usize, usize), target: &Board, target_empty: (usize, usize)) -> Result<Vec<char>, RouteError> {
+    route_inner(board, empty, target, target_empty, None)
+}
+
+// routeと同じだが，目標配置の各マスにはassignmentで決めた入力のタイルを持っていく
+// assignmentの偶奇が合っていれば，最後の2x3で行き詰まることはない
+pub fn route_with_assignment(board: &Board, empty: (usize, usize), target: &Board, target_empty: (usize, usize), assignment: &Assignment) -> Result<Vec<char>, RouteError> {
+    route_inner(board, empty, target, target_empty, Some(assignment.source_of.clone()))
+}
+
+fn route_inner(board: &Board, empty: (usize, usize), target: &Board, target_empty: (usize, usize), mut source_of: Option<Vec<usize>>) -> Result<Vec<char>, RouteError> {
+    let n = board.n;
+    // 目標配置の空きマスを右下まで動かしたものを，いったんの目標にする
+    // 揃え終わったら，同じ道を逆にたどって空きマスを戻す
+    let mut shifted = *target;
+    let mut target_empty = target_empty;
+    let mut back = vec![];
+    while target_empty != (n - 1, n - 1) {
+        let action = if target_empty.0 < n - 1 { 0 } else { 1 };
+        let next_empty = shifted.slide(target_empty, action);
+        if let Some(source_of) = source_of.as_mut() {
+            source_of.swap(target_empty.0 * n + target_empty.1, next_empty.0 * n + next_empty.1);
+        }
+        target_empty = next_empty;
+        back.push(ACTION_CHARS[reverse_action(action)]);
+    }
+
+    let mut router = Router::new(board, empty, &shifted, source_of);
+    // 上の行から1行ずつ揃える
+    for r in 0..n.saturating_sub(2) {
+        for c in 0..n - 2 {
+            router.place((r, c), (r, c))?;
+        }
+        router.place_pair((r, n - 2), (r, n - 1), (r + 1, n - 1), [1, 0])?;
+    }
+    // 最後の2行は左の列から1列ずつ揃える
+    for c in 0..n.saturating_sub(3) {
+        router.place_pair((n - 2, c), (n - 1, c), (n - 1, c + 1), [0, 1])?;
+    }
+    router.solve_last_block()?;
+
+    back.reverse();
+    Ok(router.operations.iter().map(|&action| ACTION_CHARS[action]).chain(back).collect())
+}
+
+// 揃えている途中の盤面
+// constructiveのように目標配置を揃えながら決めるときは，targetのマスを置く前に書き換えてよい
+pub struct Router {
+    pub board: Board,
+    pub empty: (usize, usize),
+    // 空きマスを右下に動かした目標配置
+    pub target: Board,
+    // 目標配置のマスごとに持っていく入力のタイル(入力でのマスの番号)．Noneなら同じ種類のどれでもよい
+    source_of: Option<Vec<usize>>,
+    // 各マスにいまあるタイルの，入力でのマスの番号
+    label: [usize; MAX_N * MAX_N],
+    // 揃え終わって動かさないマス
+    pub locked: [bool; MAX_N * MAX_N],
+    // 動かした向き
+    pub operations: Vec<usize>,
+}
+
+impl Router {
+    pub fn new(board: &Board, empty: (usize, usize), target: &Board, source_of: Option<Vec<usize>>) -> Router {
+        let mut label = [0; MAX_N * MAX_N];
+        for (p, l) in label.iter_mut().enumerate().take(board.n * board.n) {
+            *l = p;
+        }
+        Router { board: *board, empty, target: *target, source_of, label, locked: [false; MAX_N * MAX_N], operations: vec![] }
+    }
+
+    fn is_locked(&self, cell: (usize, usize)) -> bool {
+        self.locked[cell.0 * self.board.n + cell.1]
+    }
+
+    fn lock(&mut self, cell: (usize, usize), locked: bool) {
+        self.locked[cell.0 * self.board.n + cell.1] = locked;
+    }
+
+    fn slide(&mut self, action: usize) {
+        let n = self.board.n;
+        let moved = self.empty;
+        self.empty = self.board.slide(self.empty, action);
+        self.label.swap(moved.0 * n + moved.1, self.empty.0 * n + self.empty.1);
+        self.operations.push(action);
+    }
+
+    // 固定したマスを通らずに，空きマスをtoまで動かす
+    fn move_empty(&mut self, to: (usize, usize)) -> Result<(), RouteError> {
+        let actions = empty_path(&self.board, self.empty, to, &self.locked).ok_or(RouteError::Stuck { cell: to })?;
+        for action in actions {
+            self.slide(action);
+        }
+        Ok(())
+    }
+
+    // fromのタイルをtoまで動かす
+    fn move_tile(&mut self, from: (usize, usize), to: (usize, usize)) -> Result<(), RouteError> {
+        let actions = tile_path(&self.board, self.empty, from, to, &self.locked).ok_or(RouteError::Stuck { cell: to })?;
+        for action in actions {
+            self.slide(action);
+        }
+        Ok(())
+    }
+
+    // 目標配置でgoalにあるタイルを，まだ固定していないマスから選んでtoまで動かし，固定する
+    // Assignmentがなければ同じ種類のタイルを近いものから順に試す
+    pub fn place(&mut self, goal: (usize, usize), to: (usize, usize)) -> Result<(), RouteError> {
+        let n = self.board.n;
+        let tile = self.target.get(goal.0, goal.1);
+        let wanted = self.source_of.as_ref().map(|source_of| source_of[goal.0 * n + goal.1]);
+        let distance = |a: (usize, usize), b: (usize, usize)| (a.0 as i32 - b.0 as i32).abs() + (a.1 as i32 - b.1 as i32).abs();
+        let mut candidates: Vec<(usize, usize)> = (0..n * n)
+            .map(|p| (p / n, p % n))
+            .filter(|&cell| !self.is_locked(cell) && self.board.get(cell.0, cell.1) == tile)
+            .filter(|&cell| match wanted {
+                Some(w) => self.label[cell.0 * n + cell.1] == w,
+                None => true,
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(RouteError::MissingTile { tile: tile_to_char(tile), cell: to });
+        }
+        candidates.sort_by_key(|&cell| (2 * distance(cell, to) + distance(cell, self.empty), cell));
+        let mut result = Err(RouteError::Stuck { cell: to });
+        for &cell in &candidates {
+            result = self.move_tile(cell, to);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result?;
+        self.lock(to, true);
+        Ok(())
+    }
+
+    // 行(列)の最後の2マスfirst, secondをまとめて揃える
+    // 1枚ずつ置くと2枚目を入れる道がふさがるので，firstのタイルをsecondに，secondのタイルをその先のholdに置いてから，
+    // 空きマスをfirstに持ってきてactions[0], actions[1]の順に動かし，2枚をずらして入れる
+    pub fn place_pair(&mut self, first: (usize, usize), second: (usize, usize), hold: (usize, usize), actions: [usize; 2]) -> Result<(), RouteError> {
+        if self.is_placed(first, first) && self.is_placed(second, second) {
+            self.lock(first, true);
+            self.lock(second, true);
+            return Ok(());
+        }
+        self.place(first, second)?;
+        if self.place(second, hold).is_err() {
+            // secondのタイルを置いたあと，firstに残りのタイルか空きマスが閉じ込められると回り込めない
+            // いったん残りのタイルをholdの先のawayに置いてから，secondのタイルを置き直す
+            let away = (first.0 + 2 * (hold.0 - second.0), first.1 + 2 * (hold.1 - second.1));
+            self.lock(second, false);
+            self.place(second, away)?;
+            self.place(first, second)?;
+            self.lock(away, false);
+            self.place(second, hold)?;
+        }
+        self.move_empty(first)?;
+        self.slide(actions[0]);
+        self.slide(actions[1]);
+        self.lock(hold, false);
+        self.lock(first, true);
+        Ok(())
+    }
+
+    // 目標配置でgoalにあるべきタイルが，いまcellにあるか
+    fn is_placed(&self, goal: (usize, usize), cell: (usize, usize)) -> bool {
+        let n = self.board.n;
+        let same_tile = self.board.get(cell.0, cell.1) == self.target.get(goal.0, goal.1);
+        let same_label = match &self.source_of {
+            Some(source_of) => self.label[cell.0 * n + cell.1] == source_of[goal.0 * n + goal.1],
+            None => true,
+        };
+        same_tile && same_label
+    }
+
+    // 右下の2x3(Nが3未満なら盤面全体)を目標配置にする
+    fn solve_last_block(&mut self) -> Result<(), RouteError> {
+        let n = self.board.n;
+        let target = self.target;
+        let cells: Vec<usize> = (0..n * n).filter(|&p| !self.locked[p]).collect();
+        match self.solve_last_block_by(|board| cells.iter().all(|&p| board.tiles[p] == target.tiles[p]) as i32) {
+            1 => Ok(()),
+            _ => Err(RouteError::Unsolvable),
+        }
+    }
+
+    // 固定していないマスを，タイルの並びを状態とするBFSで，たどり着ける並びのうちscoreが最も大きいものにする
+    // 同じscoreなら手数の少ないもの．動かしたあとのscoreを返す
+    pub fn solve_last_block_by<F: Fn(&Board) -> i32>(&mut self, score: F) -> i32 {
+        let n = self.board.n;
+        let cells: Vec<(usize, usize)> = (0..n * n).map(|p| (p / n, p % n)).filter(|&cell| !self.is_locked(cell)).collect();
+        let start: Vec<u8> = cells.iter().map(|&(i, j)| self.board.get(i, j)).collect();
+        // 状態ごとに，直前の状態と操作を覚えておく
+        let mut prev: HashMap<Vec<u8>, (Vec<u8>, usize)> = HashMap::new();
+        prev.insert(start.clone(), (vec![], usize::MAX));
+        let mut queue = VecDeque::new();
+        queue.push_back((start.clone(), self.empty));
+        let mut best = (score(&self.board), start.clone());
+        while let Some((state, empty)) = queue.pop_front() {
+            let mut board = self.board;
+            for (&(i, j), &tile) in cells.iter().zip(&state) {
+                board.set(i, j, tile);
+            }
+            let value = score(&board);
+            if value > best.0 {
+                best = (value, state.clone());
+            }
+            let k = cells.iter().position(|&cell| cell == empty).unwrap();
+            for dir in 0..4 {
+                let next_empty = match self.board.neighbor(empty.0, empty.1, dir) {
+                    Some(cell) if !self.is_locked(cell) => cell,
+                    _ => continue,
+                };
+                let l = cells.iter().position(|&cell| cell == next_empty).unwrap();
+                let mut next = state.clone();
+                next.swap(k, l);
+                if !prev.contains_key(&next) {
+                    prev.insert(next.clone(), (state.clone(), dir));
+                    queue.push_back((next, next_empty));
+                }
+            }
+        }
+        let mut actions = vec![];
+        let mut state = best.1;
+        while state != start {
+            let (prev_state, dir) = prev.remove(&state).unwrap();
+            actions.push(dir);
+            state = prev_state;
+        }
+        for &action in actions.iter().rev() {
+            self.slide(action);
+        }
+        best.0
+    }
+}
+
+// lockedがtrueのマスを通らずに，空きマスをemptyからtoまで動かす最短の操作列
+// 届かなければNone
+fn empty_path(board: &Board, empty: (usize, usize), to: (usize, usize), locked: &[bool]) -> Option<Vec<usize>> {
+    let n = board.n;
+    let mut prev = [usize::MAX; MAX_N * MAX_N];
+    let start = empty.0 * n + empty.1;
+    prev[start] = start;
+    let mut queue = VecDeque::new();
+    queue.push_back(empty);
+    while let Some((i, j)) = queue.pop_front() {
+        if (i, j) == to {
+            break;
+        }
+        for dir in 0..4 {
+            if let Some(next) = board.neighbor(i, j, dir) {
+                if !locked[next.0 * n + next.1] && prev[next.0 * n + next.1] == usize::MAX {
+                    prev[next.0 * n + next.1] = dir;
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    if prev[to.0 * n + to.1] == usize::MAX {
+        return None;
+    }
+    // toからたどった方向を逆順に並べる
+    let mut actions = vec![];
+    let mut cell = to;
+    while cell != empty {
+        let dir = prev[cell.0 * n + cell.1];
+        actions.push(dir);
+        cell = ((cell.0 as i32 - DX[dir]) as usize, (cell.1 as i32 - DY[dir]) as usize);
+    }
+    actions.reverse();
+    Some(actions)
+}
+
+// lockedがtrueのマスを通らずに，fromのタイルをtoまで動かす最短の操作列
+// (タイルの位置, 空きマスの位置)を状態とするBFSで求める．届かなければNone
+fn tile_path(board: &Board, empty: (usize, usize), from: (usize, usize), to: (usize, usize), locked: &[bool]) -> Option<Vec<usize>> {
+    let n = board.n;
+    let cells = n * n;
+    let index = |tile: (usize, usize), empty: (usize, usize)| (tile.0 * n + tile.1) * cells + empty.0 * n + empty.1;
+    let mut prev = vec![usize::MAX; cells * cells];
+    let start = index(from, empty);
+    prev[start] = start;
+    let mut queue = VecDeque::new();
+    queue.push_back((from, empty));
+    let mut goal = None;
+    while let Some((tile, e)) = queue.pop_front() {
+        if tile == to {
+            goal = Some((tile, e));
+            break;
+        }
+        for dir in 0..4 {
+            let next_empty = match board.neighbor(e.0, e.1, dir) {
+                Some(cell) if !locked[cell.0 * n + cell.1] => cell,
+                _ => continue,
+            };
+            // 空きマスがタイルと入れ替わると，タイルは元の空きマスへ動く
+            let next_tile = if next_empty == tile { e } else { tile };
+            let next = index(next_tile, next_empty);
+            if prev[next] == usize::MAX {
+                prev[next] = dir;
+                queue.push_back((next_tile, next_empty));
+            }
+        }
+    }
+    let (mut tile, mut e) = goal?;
+    let mut actions = vec![];
+    while (tile, e) != (from, empty) {
+        let dir = prev[index(tile, e)];
+        actions.push(dir);
+        let prev_empty = ((e.0 as i32 - DX[dir]) as usize, (e.1 as i32 - DY[dir]) as usize);
+        if tile == prev_empty {
+            tile = e;
+        }
+        e = prev_empty;
+    }
+    actions.reverse();
+    Some(actions)
+}
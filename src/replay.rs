@@ -0,0 +1,63 @@
+// 出力(UDLRの操作列)を入力の盤面に対して1手ずつ再生する
+// ローカルでスコアを計算したり，盤面の途中経過を見たりするのに使う
+use crate::board::{can_slide, Board, Input};
+use crate::tiles::char_to_action;
+use std::fmt;
+
+// 再生中に見つかった不正な出力
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    // UDLR以外の文字
+    UnknownChar { turn: usize, c: char },
+    // 空きマスが盤面の外に出る操作
+    OutOfBoard { turn: usize, c: char, empty: (usize, usize) },
+    // 操作回数がTを超えている
+    TooManyMoves { len: usize, t: usize },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::UnknownChar { turn, c } => write!(f, "turn {}: unknown operation {:?} (expected one of UDLR)", turn, c),
+            ReplayError::OutOfBoard { turn, c, empty } => write!(f, "turn {}: operation {} moves the empty cell at ({}, {}) off the board", turn, c, empty.0, empty.1),
+            ReplayError::TooManyMoves { len, t } => write!(f, "too many operations: {} > T = {}", len, t),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+// 再生の結果
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub board: Board,
+    pub empty: (usize, usize),
+    // 実際に適用できた操作の数
+    pub turn: usize,
+}
+
+// 出力の空白や改行を取り除き，操作の文字だけにする
+pub fn parse_output(s: &str) -> Vec<char> {
+    s.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+// 操作列を先頭からturn手だけ再生する
+// 不正な操作があればその手番を含むエラーを返す
+pub fn replay(input: &Input, operation_list: &[char], turn: usize) -> Result<Replay, ReplayError> {
+    if operation_list.len() > input.t {
+        return Err(ReplayError::TooManyMoves { len: operation_list.len(), t: input.t });
+    }
+    let (mut board, mut empty) = Board::from_chars(&input.board);
+    for (i, &c) in operation_list.iter().take(turn).enumerate() {
+        let action = match char_to_action(c) {
+            Some(action) => action,
+            None => return Err(ReplayError::UnknownChar { turn: i + 1, c }),
+        };
+        // 直前の手を相殺する手も公式ルールでは合法なので，盤面の範囲だけを見る
+        if !can_slide(empty, action, input.n) {
+            return Err(ReplayError::OutOfBoard { turn: i + 1, c, empty });
+        }
+        empty = board.slide(empty, action);
+    }
+    Ok(Replay { board, empty, turn: turn.min(operation_list.len()) })
+}
@@ -0,0 +1,20 @@
+// 公式ルールに沿った得点計算
+use crate::board::Board;
+use crate::evaluation::analyze_components;
+
+// 得点を計算する関数
+// 全域木が完成していなければ木の大きさに比例し，完成していれば手番が少ないほど高い
+pub fn compute_score(operation: f64, tree_size: f64, n: f64, t: f64) -> i32 {
+    let score = if tree_size < n.powf(2.0) - 1.0 {
+        (500000.0 * tree_size / (n.powf(2.0) - 1.0)).round()
+    } else {
+        (500000.0 * (2.0 - operation / t)).round()
+    };
+    score as i32
+}
+
+// 盤面全体で最大の木(ループを持たない連結成分)の大きさを求める
+// 公式の得点と一致させたいときに使う
+pub fn compute_official_tree_size(board: &Board) -> i32 {
+    analyze_components(board).max_tree_size()
+}
@@ -0,0 +1,462 @@
+// 探索の戦略たち
+// どれも盤面の操作や評価はboard/evaluationに任せ，どの状態を残すかだけを決める
+use crate::arrangement::ArrangementAnnealing;
+use crate::bidirectional::BidirectionalSearch;
+use crate::board::{get_legal_actions, is_legal_action, Board, GameState, Input, Moves};
+use crate::constructive::construct;
+use crate::evaluation::analyze_components;
+use crate::evaluator::Evaluator;
+use crate::ida::IdaStar;
+use crate::parity::plan_assignment;
+use crate::pdb::{PatternDatabase, MEMORY_BUDGET};
+use crate::puzzle::{route, route_with_assignment};
+use crate::scoring::{compute_official_tree_size, compute_score};
+use crate::tiles::{char_to_action, reverse_action, ACTION_CHARS};
+use crate::zobrist::SeenStates;
+use crate::{Solver, SolverRng};
+use ordered_float::OrderedFloat;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+
+// 山登り法
+// 空きタイルの上下左右のタイルをランダムに引っ張ってきて，木が大きくなったら採用する
+// 回数制限tまで登ったらそれまでのベストの手順を保存し，再度1からシミュレートし始める
+pub struct HillClimbing {
+    // 1からシミュレートし直す回数
+    pub epoch: usize,
+    pub rng: SolverRng,
+}
+
+impl Solver for HillClimbing {
+    fn solve(&mut self, input: &Input) -> Vec<char> {
+        let n = input.n;
+        let mut max_tree_size = 0;
+        let mut max_tree_operation = vec![];
+        for _ in 0..self.epoch {
+            let (mut board, mut empty) = Board::from_chars(&input.board);
+            // 初期の木のサイズを取得
+            // 空きマスから測るので1とする
+            let mut prev_tree_size = 1;
+            // 手番を逐次保存していく
+            let mut operation_list = vec![];
+            // 回数制限まで以下シミュレートする
+            for _ in 0..input.t {
+                // 方向のインデックスをシャッフルし，順に木を評価する
+                // どれを引っぱっても大きくならなければ，最後を採用する
+                let mut dir_index = get_legal_actions(&operation_list, empty, n);
+                dir_index.shuffle(&mut self.rng);
+                for (i, &dir) in dir_index.iter().enumerate() {
+                    let next_empty = board.slide(empty, dir);
+                    // 元の空きマス(いま動かしたタイル)を始点に，木の大きさを測る
+                    // ループがあれば木ではないので大きさ0とみなす
+                    let analysis = analyze_components(&board);
+                    let component = analysis.component_at(empty.0, empty.1).unwrap();
+                    let next_tree_size = if component.has_cycle {
+                        0
+                    } else {
+                        component.tile_count as i32
+                    };
+                    if next_tree_size > prev_tree_size || i == dir_index.len() - 1 {
+                        operation_list.push(ACTION_CHARS[dir]);
+                        // 盤面全体で最大の木が歴代最大を更新したら，手番を保存する
+                        if max_tree_size < analysis.max_tree_size() {
+                            max_tree_size = analysis.max_tree_size();
+                            max_tree_operation = operation_list.clone();
+                        }
+                        // 次のループのためにprevとemptyを更新する
+                        prev_tree_size = next_tree_size;
+                        empty = next_empty;
+                        break;
+                    }
+                    // 盤面のタイルをもとに戻す
+                    board.slide(next_empty, reverse_action(dir));
+                }
+            }
+        }
+        max_tree_operation
+    }
+}
+
+// ビームサーチ
+// 各手番で評価値が高い順にbeam_width個の状態を残し，それらの合法手をすべて試す
+pub struct BeamSearch {
+    // 各探索時点においていくつの状態を保持することができるか
+    pub beam_width: usize,
+    // 展開済みの盤面を覚えておく数の上限
+    pub seen_capacity: usize,
+    // 優先度付きキューの順番を決める評価関数
+    pub evaluator: Box<dyn Evaluator>,
+}
+
+impl Solver for BeamSearch {
+    fn solve(&mut self, input: &Input) -> Vec<char> {
+        let n = input.n;
+        let mut max_tree_size = 0;
+        let mut max_tree_operation = Moves::new();
+        // ビームサーチに使用する優先度付きキュー
+        let mut current_beam = BinaryHeap::new();
+        current_beam.push(GameState::new(input));
+        let mut seen = SeenStates::new(self.seen_capacity);
+        // 回数制限(探索木の深さの限界)まで以下シミュレートする
+        for depth in 0..input.t {
+            let mut next_beam = BinaryHeap::new();
+            // ビーム幅分状態を保持する
+            for _ in 0..self.beam_width {
+                // 展開済みの盤面は飛ばす
+                let current_game_state = match pop_unseen(&mut current_beam, &mut seen, depth) {
+                    Some(state) => state,
+                    None => break,
+                };
+                for action in current_game_state.get_legal_actions() {
+                    let mut next_game_state = current_game_state.clone();
+                    next_game_state.advance(action);
+                    next_game_state.evaluated_score = OrderedFloat(self.evaluator.evaluate(&next_game_state));
+                    next_beam.push(next_game_state);
+                }
+            }
+            current_beam = next_beam;
+            // 評価値の順位に関係なく，盤面全体で最大の木が一番大きい状態を見る
+            let best_state = match current_beam.iter().max_by_key(|state| state.max_tree_size) {
+                Some(state) => state,
+                None => break,
+            };
+            // 歴代最大を更新したら，手番を保存する
+            if max_tree_size < best_state.max_tree_size {
+                max_tree_size = best_state.max_tree_size;
+                max_tree_operation = best_state.operation_list.clone();
+            }
+            if max_tree_size as usize == n*n - 1 {
+                break;
+            }
+        }
+        max_tree_operation.to_chars()
+    }
+}
+
+// chokudaiサーチ
+// 手番ごとに優先度付きキューを持ち，浅い手番から1つずつ取り出して次の手番に展開する
+// これを時間いっぱい繰り返すことで，一度深くまで探索しても浅い位置から別の手をやり直せる
+pub struct ChokudaiSearch {
+    // 浅いところから探索をやり直す回数の上限
+    // 時間で打ち切るならusize::MAXでよい
+    pub beam_number: usize,
+    // 探索する深さ(手数)の上限
+    pub beam_depth: usize,
+    // 各手番で1回に取り出す状態の数
+    pub beam_width: usize,
+    // 取り出した後，その手番の残りの状態を捨てる確率
+    // 評価値の高い状態ばかりを見続けないようにするため
+    pub discard_probability: f64,
+    // この時刻を過ぎたら探索を打ち切り，それまでのベストを返す
+    pub deadline: Instant,
+    // 展開済みの盤面を覚えておく数の上限
+    pub seen_capacity: usize,
+    // 優先度付きキューの順番を決める評価関数
+    pub evaluator: Box<dyn Evaluator>,
+    pub rng: SolverRng,
+}
+
+// 時刻を確認する間隔(手番の数)
+// Instant::now()は軽くはないので，毎手番は見ない
+const TIME_CHECK_INTERVAL: usize = 64;
+
+impl Solver for ChokudaiSearch {
+    fn solve(&mut self, input: &Input) -> Vec<char> {
+        let mut max_tree_size = 0;
+        let mut max_tree_operation = Moves::new();
+
+        // 優先度付きキューの配列．各beamの各ターンで全手番を記憶し，各beamで逐次高いものを取り出す
+        let mut beam = vec![BinaryHeap::new(); self.beam_depth + 1];
+        beam[0].push(GameState::new(input));
+        let mut seen = SeenStates::new(self.seen_capacity);
+        'search: for _ in 0..self.beam_number {
+            for depth in 0..self.beam_depth {
+                if depth % TIME_CHECK_INTERVAL == 0 && Instant::now() >= self.deadline {
+                    break 'search;
+                }
+                for _ in 0..self.beam_width {
+                    // 手番depthの中で評価値が最も高いものを取り出す
+                    // 同じ盤面を同じかより浅い手番で展開済みなら飛ばす
+                    let now_state = match pop_unseen(&mut beam[depth], &mut seen, depth) {
+                        Some(state) => state,
+                        None => break,
+                    };
+                    if self.rng.gen_range(0.0, 1.0) < self.discard_probability {
+                        beam[depth].clear();
+                    }
+                    // 取りうるアクションをすべて行う
+                    let mut legal_actions = now_state.get_legal_actions();
+                    // 各アクション後の評価値が同じ時，いつも同じ順番でnext_stateがheapに突っ込まれるのを防いでみる
+                    legal_actions.shuffle(&mut self.rng);
+                    for &action in &legal_actions {
+                        let mut next_state = now_state.clone();
+                        next_state.advance(action);
+                        next_state.evaluated_score = OrderedFloat(self.evaluator.evaluate(&next_state));
+                        // もし盤面全体で最大の木の大きさを更新するなら手順を保存する
+                        if max_tree_size < next_state.max_tree_size {
+                            max_tree_size = next_state.max_tree_size;
+                            max_tree_operation = next_state.operation_list.clone();
+                        }
+                        // 次のループのために配列にstateを保存する
+                        beam[depth+1].push(next_state);
+                    }
+                }
+            }
+        }
+        max_tree_operation.to_chars()
+    }
+}
+
+// 目標配置を決めてから，スライドパズルとして動かす
+// 全域木になる配置を焼きなましで探し，見つかったら定石どおりにそこまで動かす
+// 動かせない配置やT手を超える配置だったら，別の配置を探し直す
+pub struct PuzzleSearch {
+    // 目標配置の探索．空きマスの位置もここで決める
+    pub annealing: ArrangementAnnealing,
+    // ルーターの答えを両方向探索で縮める区間の手数．0なら縮めない
+    pub shortcut_window: usize,
+    // 最後の2行をpattern database付きのIDA*で解き直すときに展開するノードの数の上限．0なら解き直さない
+    pub last_rows_node_limit: usize,
+}
+
+// 区間ごとの両方向探索で覚える盤面の数の上限
+const SHORTCUT_NODE_LIMIT: usize = 200_000;
+
+// 最後の2行を解き直すのに使う時間(pattern databaseを作る時間も含む)は，残り時間のこの割合まで
+// 残りは両方向探索で縮めるのに使う
+const LAST_ROWS_TIME_DIVISOR: u32 = 4;
+
+// ルーターの答えのうち，上の行を揃え終わって最後の2行だけを動かしている部分を，
+// pattern databaseを下界に使ったIDA*の最短手順に置き換える
+// 表を作り終わらないか，探索が上限までに終わらなければ元の手順のまま返す
+fn finish_last_rows(board: &Board, empty: (usize, usize), target: &Board, target_empty: (usize, usize), operations: Vec<char>, node_limit: usize, deadline: Instant) -> Vec<char> {
+    let n = board.n;
+    let movable: Vec<bool> = (0..n * n).map(|p| p / n + 2 >= n).collect();
+    // 空きマスが最後に上の行から出た手の次からは，最後の2行だけを動かしている
+    let (mut current, mut current_empty) = (*board, empty);
+    let mut split = (0, current, current_empty);
+    for (turn, &c) in operations.iter().enumerate() {
+        let upper = current_empty.0 + 2 < n;
+        current_empty = current.slide(current_empty, char_to_action(c).unwrap());
+        if upper {
+            split = (turn + 1, current, current_empty);
+        }
+    }
+    let (split, start, start_empty) = split;
+    if (0..n * n).any(|p| !movable[p] && start.tiles[p] != target.tiles[p]) {
+        return operations;
+    }
+    let now = Instant::now();
+    let deadline = now + deadline.saturating_duration_since(now) / LAST_ROWS_TIME_DIVISOR;
+    let database = match PatternDatabase::build(target, target_empty, &movable, MEMORY_BUDGET, deadline) {
+        Ok(database) => database,
+        Err(_) => return operations,
+    };
+    match (IdaStar { node_limit, deadline }).solve_with_database(&start, start_empty, &database) {
+        Ok(tail) if split + tail.len() < operations.len() => operations[..split].iter().chain(&tail).copied().collect(),
+        _ => operations,
+    }
+}
+
+// 操作列のT手目までの先頭部分のうち，公式の得点が最もよいものを得点と一緒に返す
+// 全域木になるまでは手数によらず木の大きさで得点が決まるので，1手ずつ再生して比べる
+fn best_prefix(input: &Input, operations: &[char]) -> (i32, Vec<char>) {
+    let (n, t) = (input.n as f64, input.t as f64);
+    let mut state = GameState::new(input);
+    let mut best = (compute_score(0.0, compute_official_tree_size(&state.board) as f64, n, t), 0);
+    for (turn, &c) in operations.iter().take(input.t).enumerate() {
+        state.advance(char_to_action(c).unwrap());
+        let score = compute_score((turn + 1) as f64, state.max_tree_size as f64, n, t);
+        if score > best.0 {
+            best = (score, turn + 1);
+        }
+    }
+    (best.0, operations[..best.1].to_vec())
+}
+
+impl Solver for PuzzleSearch {
+    // T手以内に目標配置まで動かせなかったら，それまでの手順の先頭部分で得点が最もよいものを返す
+    // 全域木の配置が見つからなくても，コストが最も小さかった配置へ動かす途中で木は大きくなるので，その先頭部分を使う
+    fn solve(&mut self, input: &Input) -> Vec<char> {
+        let (board, empty) = Board::from_chars(&input.board);
+        let mut fallback = best_prefix(input, &[]);
+        loop {
+            let arrangement = self.annealing.search(&board);
+            // 同じ種類のタイルをその場で選ぶ動かし方と，どのタイルをどのマスへ持っていくかを
+            // 移動距離が小さくなるよう先に決める動かし方の，手数が少ないほうを使う
+            // 目標配置が遠いと前者のほうが短くなりやすいが，前者は最後の2x3で偶奇が合わないことがある
+            let greedy = route(&board, empty, &arrangement.board, arrangement.empty).ok();
+            let assigned = plan_assignment(&board, empty, &arrangement.board, arrangement.empty)
+                .ok()
+                .and_then(|assignment| route_with_assignment(&board, empty, &arrangement.board, arrangement.empty, &assignment).ok());
+            if let Some(mut operations) = greedy.into_iter().chain(assigned).min_by_key(|operations| operations.len()) {
+                if arrangement.is_spanning_tree() {
+                    if self.last_rows_node_limit > 0 {
+                        operations = finish_last_rows(&board, empty, &arrangement.board, arrangement.empty, operations, self.last_rows_node_limit, self.annealing.deadline);
+                    }
+                    // ルーターは1枚ずつ運ぶので遠回りが多い．短い区間ごとに最短の手順へ置き換える
+                    if self.shortcut_window > 0 {
+                        let shortcut = BidirectionalSearch { node_limit: SHORTCUT_NODE_LIMIT, deadline: self.annealing.deadline };
+                        operations = shortcut.shorten(&board, empty, &operations, self.shortcut_window);
+                    }
+                    if operations.len() <= input.t {
+                        return operations;
+                    }
+                }
+                let prefix = best_prefix(input, &operations);
+                if prefix.0 > fallback.0 {
+                    fallback = prefix;
+                }
+            }
+            if Instant::now() >= self.annealing.deadline {
+                break;
+            }
+        }
+        fallback.1
+    }
+}
+
+// 操作列の焼きなまし法
+// 操作列の一部を置き換える･挿入する･削除する変更を加え，最後まで再生した盤面の公式の得点で受理するかを決める
+// 変更で合法でなくなった手は，再生しながら取り除く
+pub struct SequenceAnnealing {
+    // 一度に置き換える･挿入する･削除する手数の上限
+    pub max_segment_len: usize,
+    // 温度の初期値と最終値(得点の単位)
+    pub start_temperature: f64,
+    pub end_temperature: f64,
+    // この時刻を過ぎたら打ち切り，それまでのベストを返す
+    pub deadline: Instant,
+    pub rng: SolverRng,
+}
+
+impl SequenceAnnealing {
+    // 操作列の変更後の候補を作る
+    // 先頭からstart手目までは変えない
+    fn mutate(&mut self, operation_list: &[char], start: usize) -> Vec<char> {
+        let len = self.rng.gen_range(1, self.max_segment_len + 1);
+        let end = (start + len).min(operation_list.len());
+        let mut candidate = operation_list[..start].to_vec();
+        let random_segment = |rng: &mut SolverRng| (0..len).map(|_| ACTION_CHARS[rng.gen_range(0, 4)]).collect::<Vec<char>>();
+        match self.rng.gen_range(0, 3) {
+            // 置き換え
+            0 => {
+                candidate.extend(random_segment(&mut self.rng));
+                candidate.extend_from_slice(&operation_list[end..]);
+            }
+            // 挿入
+            1 => {
+                candidate.extend(random_segment(&mut self.rng));
+                candidate.extend_from_slice(&operation_list[start..]);
+            }
+            // 削除
+            _ => candidate.extend_from_slice(&operation_list[end..]),
+        }
+        candidate
+    }
+}
+
+impl Solver for SequenceAnnealing {
+    fn solve(&mut self, input: &Input) -> Vec<char> {
+        let (n, t) = (input.n, input.t);
+        let start_time = Instant::now();
+        let duration = self.deadline.saturating_duration_since(start_time).as_secs_f64().max(1e-9);
+        let score_of = |board: &Board, turn: usize| compute_score(turn as f64, compute_official_tree_size(board) as f64, n as f64, t as f64);
+
+        // states[k]はk手目まで再生した盤面と空きマス
+        // 変更した位置より前は再生し直さずに済むよう，すべて覚えておく
+        let mut operation_list: Vec<char> = vec![];
+        let mut states = vec![Board::from_chars(&input.board)];
+        let mut score = score_of(&states[0].0, 0);
+        let mut best_score = score;
+        let mut best_operation_list = vec![];
+        let mut temperature = self.start_temperature;
+        let mut iteration = 0;
+        loop {
+            if iteration % TIME_CHECK_INTERVAL == 0 {
+                let progress = start_time.elapsed().as_secs_f64() / duration;
+                if progress >= 1.0 {
+                    break;
+                }
+                temperature = self.start_temperature * (self.end_temperature / self.start_temperature).powf(progress);
+            }
+            iteration += 1;
+
+            let start = self.rng.gen_range(0, operation_list.len() + 1);
+            let mut candidate = self.mutate(&operation_list, start);
+            // start手目から再生し直し，合法でない手とT手を超えた分を取り除く
+            let (mut board, mut empty) = states[start];
+            let mut next_states = vec![];
+            let mut len = start;
+            for k in start..candidate.len() {
+                if len >= t {
+                    break;
+                }
+                let c = candidate[k];
+                let action = char_to_action(c).unwrap();
+                let last = if len == 0 { None } else { Some(candidate[len - 1]) };
+                if !is_legal_action(last, empty, action, n) {
+                    continue;
+                }
+                empty = board.slide(empty, action);
+                candidate[len] = c;
+                len += 1;
+                next_states.push((board, empty));
+            }
+            candidate.truncate(len);
+
+            let next_score = score_of(&board, len);
+            let delta = (next_score - score) as f64;
+            if delta >= 0.0 || self.rng.gen_range(0.0, 1.0) < (delta / temperature).exp() {
+                operation_list = candidate;
+                states.truncate(start + 1);
+                states.extend(next_states);
+                score = next_score;
+                if score > best_score {
+                    best_score = score;
+                    best_operation_list = operation_list.clone();
+                }
+            }
+        }
+        best_operation_list
+    }
+}
+
+// 1行ずつ木を作りながら揃える構築的な解法
+// 行に置くタイルの種類の選び方を乱択で変えながら時間いっぱい作り直し，公式の得点が最もよいものを返す
+// T手を超えたらT手目までで打ち切った盤面で比べる
+pub struct RowByRowSearch {
+    pub deadline: Instant,
+    pub rng: SolverRng,
+}
+
+impl Solver for RowByRowSearch {
+    // 締め切りで作りかけになった盤面も，T手を超えた手順も，先頭部分で得点が最もよいものを候補にする
+    fn solve(&mut self, input: &Input) -> Vec<char> {
+        let mut best = best_prefix(input, &[]);
+        loop {
+            if let Ok(state) = construct(input, &mut self.rng, self.deadline) {
+                let candidate = best_prefix(input, &state.operation_list.to_chars());
+                if candidate.0 > best.0 {
+                    best = candidate;
+                }
+            }
+            if Instant::now() >= self.deadline {
+                break;
+            }
+        }
+        best.1
+    }
+}
+
+// 優先度付きキューから，まだ展開していない盤面のうち評価値が最も高いものを取り出す
+fn pop_unseen(heap: &mut BinaryHeap<GameState>, seen: &mut SeenStates, depth: usize) -> Option<GameState> {
+    while let Some(state) = heap.pop() {
+        if seen.visit(state.hash, depth) {
+            return Some(state);
+        }
+    }
+    None
+}
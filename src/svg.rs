@@ -0,0 +1,53 @@
+// 盤面をSVGで描画する
+// Web版のビジュアライザを使わずに，手元で盤面の途中経過を確認するため
+use crate::board::Board;
+use crate::tiles::DIR_BITS;
+use crate::evaluation::{classify_tiles, find_cycles, TileClass};
+use std::fmt::Write;
+
+// 1タイルの大きさ(px)
+const CELL: usize = 40;
+// 道の太さ(px)
+const PIPE: usize = 10;
+
+// 盤面をSVGの文字列にする
+// 空きマスは灰色，最大の木は緑，ループを含む連結成分は赤で描く
+// さらに独立な閉路それぞれを，タイルの中心を結ぶ破線で重ねて描く
+pub fn render_svg(board: &Board) -> String {
+    let n = board.n;
+    let class = classify_tiles(board);
+    let size = CELL * n;
+    let mut svg = String::new();
+    writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", size, size, size, size).unwrap();
+    for (i, row) in class.iter().enumerate() {
+        for (j, &tile_class) in row.iter().enumerate() {
+            let (x, y) = (CELL * j, CELL * i);
+            let (background, pipe) = match tile_class {
+                TileClass::Empty => ("#555555", "#555555"),
+                TileClass::Normal => ("#f0f0f0", "#8a6a3a"),
+                TileClass::LargestTree => ("#e6f5e6", "#2e8b57"),
+                TileClass::Loop => ("#fbe4e4", "#d03030"),
+            };
+            writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"#999999\"/>", x, y, CELL, CELL, background).unwrap();
+            if tile_class == TileClass::Empty {
+                continue;
+            }
+            // タイルの上下左右に道があれば，中心からその辺まで線を引く
+            // 0~3の順番で，下右上左です．
+            let (cx, cy) = (x + CELL / 2, y + CELL / 2);
+            let ends = [(cx, y + CELL), (x + CELL, cy), (cx, y), (x, cy)];
+            for (dir, &(ex, ey)) in ends.iter().enumerate() {
+                if board.get(i, j) & DIR_BITS[dir] != 0 {
+                    writeln!(svg, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>", cx, cy, ex, ey, pipe, PIPE).unwrap();
+                }
+            }
+            writeln!(svg, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>", cx, cy, PIPE / 2, pipe).unwrap();
+        }
+    }
+    for cycle in find_cycles(board) {
+        let points: Vec<String> = cycle.cells.iter().map(|&(i, j)| format!("{},{}", CELL * j + CELL / 2, CELL * i + CELL / 2)).collect();
+        writeln!(svg, "<polygon class=\"cycle\" points=\"{}\" fill=\"none\" stroke=\"#ff00ff\" stroke-width=\"3\" stroke-dasharray=\"6,3\"/>", points.join(" ")).unwrap();
+    }
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
@@ -0,0 +1,34 @@
+// タイルと操作に関する定義
+
+// グラフ上で進める方向の定義
+// 0~3の順番で，下右上左です．
+pub const DX: [i32; 4] = [1, 0, -1, 0];
+pub const DY: [i32; 4] = [0, 1, 0, -1];
+
+// 各方向に対応する操作の文字
+pub const ACTION_CHARS: [char; 4] = ['D', 'R', 'U', 'L'];
+
+// 各方向に道があることを示すタイルのビット
+// タイルの番号は左1, 上2, 右4, 下8のビットの和
+pub const DIR_BITS: [u8; 4] = [8, 4, 2, 1];
+
+// 操作の文字を方向のインデックスに変換する
+pub fn char_to_action(c: char) -> Option<usize> {
+    ACTION_CHARS.iter().position(|&a| a == c)
+}
+
+// ある方向の逆方向を返す
+// 例えばDの後にUをやると何もしないのと同じになる
+pub fn reverse_action(action: usize) -> usize {
+    (action + 2) % 4
+}
+
+// 入力の16進数1文字をタイルのビットに変換する
+pub fn char_to_tile(c: char) -> u8 {
+    c.to_digit(16).unwrap() as u8
+}
+
+// タイルのビットを入力と同じ16進数1文字に戻す
+pub fn tile_to_char(tile: u8) -> char {
+    std::char::from_digit(tile as u32, 16).unwrap()
+}
@@ -0,0 +1,91 @@
+// Zobrist hashによる盤面のハッシュ化と，一度展開した盤面を覚えておく集合
+// 同じ盤面を何度も展開しないようにして，探索の多様性を上げる
+use crate::board::{Board, MAX_N};
+use lazy_static::lazy_static;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use rustc_hash::FxHashMap;
+
+// ハッシュ表の乱数はシードを固定して作る(実行ごとに変わらないように)
+const ZOBRIST_SEED: u64 = 0xa11c_0011;
+
+struct ZobristTable {
+    // タイルの種類 x マス
+    tile: [[u64; MAX_N * MAX_N]; 16],
+    // 空きマスの位置
+    empty: [u64; MAX_N * MAX_N],
+}
+
+lazy_static! {
+    static ref TABLE: ZobristTable = {
+        let mut rng = Pcg64Mcg::seed_from_u64(ZOBRIST_SEED);
+        let mut tile = [[0; MAX_N * MAX_N]; 16];
+        for row in tile.iter_mut() {
+            for h in row.iter_mut() {
+                *h = rng.gen();
+            }
+        }
+        let mut empty = [0; MAX_N * MAX_N];
+        for h in empty.iter_mut() {
+            *h = rng.gen();
+        }
+        ZobristTable { tile, empty }
+    };
+}
+
+// 盤面全体のハッシュを計算する
+pub fn hash_board(board: &Board, empty: (usize, usize)) -> u64 {
+    let n = board.n;
+    let mut hash = TABLE.empty[empty.0 * n + empty.1];
+    for i in 0..n {
+        for j in 0..n {
+            hash ^= TABLE.tile[board.get(i, j) as usize][i * n + j];
+        }
+    }
+    hash
+}
+
+// tileがfromから空きマスtoに動いたときのハッシュの差分
+// fromが新しい空きマスになる
+pub fn slide_diff(n: usize, tile: u8, from: (usize, usize), to: (usize, usize)) -> u64 {
+    let (f, t) = (from.0 * n + from.1, to.0 * n + to.1);
+    TABLE.tile[tile as usize][f] ^ TABLE.tile[0][f] ^ TABLE.tile[0][t] ^ TABLE.tile[tile as usize][t] ^ TABLE.empty[f] ^ TABLE.empty[t]
+}
+
+// 一度展開した盤面と，そのときの手番を覚えておく集合
+// メモリ制限に収まるよう，capacityを超えたら新しい盤面は覚えない
+pub struct SeenStates {
+    depth_of: FxHashMap<u64, u32>,
+    capacity: usize,
+}
+
+impl SeenStates {
+    pub fn new(capacity: usize) -> SeenStates {
+        SeenStates { depth_of: FxHashMap::default(), capacity }
+    }
+
+    // hashの盤面を手番depthで展開してよいか
+    // 同じ盤面を同じかより浅い手番で展開済みならfalseを返す
+    pub fn visit(&mut self, hash: u64, depth: usize) -> bool {
+        let depth = depth as u32;
+        if let Some(d) = self.depth_of.get_mut(&hash) {
+            if *d <= depth {
+                return false;
+            }
+            *d = depth;
+            return true;
+        }
+        if self.depth_of.len() < self.capacity {
+            self.depth_of.insert(hash, depth);
+        }
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.depth_of.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.depth_of.is_empty()
+    }
+}
@@ -0,0 +1,771 @@
+use ahc011::arrangement::{arrangement_cost, ArrangementAnnealing};
+use ahc011::bidirectional::{BidirectionalError, BidirectionalSearch};
+use ahc011::board::{get_legal_actions, parse_input, Board, GameState, Input, Moves};
+use ahc011::Solver;
+use ahc011::config::new_rng;
+use ahc011::constructive::{construct, Builder};
+use ahc011::gen::gen;
+use ahc011::evaluation::{analyze_components, count_dangling, find_cycles};
+use ahc011::evaluator::{Evaluator, Features, WeightedEvaluator, Weights};
+use ahc011::hungarian::hungarian;
+use ahc011::ida::{lower_bound, IdaError, IdaStar};
+use ahc011::parity::{check_reachability, plan_assignment, Assignment, ParityError};
+use ahc011::pdb::{PatternDatabase, PdbError};
+use ahc011::puzzle::{route, route_with_assignment, RouteError};
+use ahc011::replay::{parse_output, replay, ReplayError};
+use ahc011::scoring::{compute_official_tree_size, compute_score};
+use ahc011::search::{PuzzleSearch, RowByRowSearch, SequenceAnnealing};
+use ahc011::svg::render_svg;
+use ahc011::tiles::{char_to_action, reverse_action, ACTION_CHARS};
+use ahc011::zobrist::{hash_board, SeenStates};
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+// 問題文のサンプル1
+fn sample_input() -> Input {
+    let board = ["62ce43", "a068f9", "a89da9", "5d93cb", "276253", "424ba8"];
+    Input {
+        n: 6,
+        t: 432,
+        board: board.iter().map(|line| line.chars().collect()).collect(),
+    }
+}
+
+#[test]
+fn sample_output_is_replayable() {
+    let input = sample_input();
+    let mut state = GameState::new(&input);
+    assert_eq!(state.empty, (1, 1));
+    for c in "RRRDLUULDDDDLUUUR".chars() {
+        let action = char_to_action(c).unwrap();
+        assert!(state.get_legal_actions().contains(&action));
+        state.advance(action);
+    }
+    assert_eq!(state.turn, 17);
+    assert_eq!(state.operation_list.to_chars().into_iter().collect::<String>(), "RRRDLUULDDDDLUUUR");
+    assert_eq!(state.empty, (1, 2));
+    // 空きマスは道を持たない
+    assert_eq!(state.board.get(1, 2), 0);
+}
+
+#[test]
+fn moves_are_packed_across_words() {
+    // 1語に32手入るので，境界をまたいで詰めて切り詰めても手順が変わらない
+    let actions: Vec<usize> = (0..70).map(|i| i * 7 % 4).collect();
+    let mut moves = Moves::new();
+    for &action in &actions {
+        moves.push(action);
+    }
+    assert_eq!(moves.iter().collect::<Vec<_>>(), actions);
+    assert_eq!(moves.last(), Some(actions[69]));
+    moves.truncate(33);
+    assert_eq!(moves.iter().collect::<Vec<_>>(), actions[..33]);
+    moves.truncate(32);
+    moves.push(3);
+    assert_eq!(moves.get(32), 3);
+    assert_eq!(moves.to_chars()[..32], actions[..32].iter().map(|&action| ACTION_CHARS[action]).collect::<Vec<_>>()[..]);
+}
+
+#[test]
+fn spanning_tree_is_measured_from_any_tile() {
+    // c 1
+    // 2 0 のように3枚で全域木を作る
+    let board = vec![vec!['c', '1'], vec!['2', '0']];
+    let (board, empty) = Board::from_chars(&board);
+    assert_eq!(empty, (1, 1));
+    let component = *analyze_components(&board).component_at(0, 1).unwrap();
+    assert!(!component.has_cycle);
+    assert_eq!((component.tile_count, component.edge_count, component.rectangle_area()), (3, 2, 4));
+}
+
+#[test]
+fn score_is_proportional_to_tree_size_before_completion() {
+    assert_eq!(compute_score(0.0, 0.0, 6.0, 432.0), 0);
+    assert_eq!(compute_score(100.0, 7.0, 6.0, 432.0), 100000);
+    assert_eq!(compute_score(432.0, 35.0, 6.0, 432.0), 500000);
+}
+
+#[test]
+fn legal_actions_exclude_walls_and_undo() {
+    // 左上の角にいるときは下と右しか動けない
+    assert_eq!(get_legal_actions(&[], (0, 0), 6), vec![0, 1]);
+    // 直前にRをしたらLは相殺するので選ばない
+    assert_eq!(get_legal_actions(&['R'], (2, 2), 6), vec![0, 1, 2]);
+}
+
+#[test]
+fn loop_is_detected_in_ring_of_tiles() {
+    // c 9
+    // 6 3 のように4枚で輪を作る
+    let board = vec![vec!['c', '9'], vec!['6', '3']];
+    let (board, _) = Board::from_chars(&board);
+    let component = *analyze_components(&board).component_at(0, 0).unwrap();
+    assert!(component.has_cycle);
+    assert_eq!((component.tile_count, component.edge_count), (4, 4));
+}
+
+#[test]
+fn replay_reports_illegal_operations() {
+    let input = sample_input();
+    let err = replay(&input, &parse_output("RRX\n"), 3).unwrap_err();
+    assert_eq!(err, ReplayError::UnknownChar { turn: 3, c: 'X' });
+    let err = replay(&input, &parse_output("UU"), 2).unwrap_err();
+    assert_eq!(err, ReplayError::OutOfBoard { turn: 2, c: 'U', empty: (0, 1) });
+    let err = replay(&input, &vec!['R'; 433], 433).unwrap_err();
+    assert_eq!(err, ReplayError::TooManyMoves { len: 433, t: 432 });
+}
+
+#[test]
+fn replay_matches_game_state() {
+    let input = sample_input();
+    let operation_list = parse_output("RRRDLUULDDDDLUUUR");
+    let mut state = GameState::new(&input);
+    for &c in &operation_list {
+        state.advance(char_to_action(c).unwrap());
+    }
+    let result = replay(&input, &operation_list, operation_list.len()).unwrap();
+    assert_eq!(result.board, state.board);
+    assert_eq!(result.empty, state.empty);
+    assert_eq!(compute_official_tree_size(&result.board), 14);
+}
+
+#[test]
+fn generated_case_is_deterministic_spanning_tree() {
+    let input = gen(42, Some(7));
+    assert_eq!(input.to_string(), gen(42, Some(7)).to_string());
+    assert_eq!((input.n, input.t), (7, 686));
+    assert_eq!(parse_input(&input.to_string()).board, input.board);
+    // N^2-1頂点の全域木なので，道の端点の数は辺の数の2倍になる
+    let half_edges: u32 = input.board.iter().flatten().map(|c| c.to_digit(16).unwrap().count_ones()).sum();
+    assert_eq!(half_edges, 2 * (7 * 7 - 2));
+    assert_eq!(input.board.iter().flatten().filter(|&&c| c == '0').count(), 1);
+}
+
+#[test]
+fn svg_marks_loops_and_empty_cell() {
+    // 上2行がループ，下の段は空きマスと単独のタイル
+    let board = vec![vec!['c', '9', '0'], vec!['6', '3', '0'], vec!['0', '0', '8']];
+    let (board, _) = Board::from_chars(&board);
+    let svg = render_svg(&board);
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("fill=\"#fbe4e4\"").count(), 4);
+    assert_eq!(svg.matches("fill=\"#555555\"").count(), 4);
+    assert_eq!(svg.matches("fill=\"#e6f5e6\"").count(), 1);
+}
+
+#[test]
+fn half_pipes_do_not_connect_tiles() {
+    // 右に道がある4の隣に，左に道のない8を置いてもつながらない
+    let (board, _) = Board::from_chars(&[vec!['4', '8'], vec!['0', '2']]);
+    assert!(!board.is_connected(0, 0, 1));
+    assert!(!board.is_connected(0, 1, 3));
+    assert_eq!(analyze_components(&board).component_at(0, 0).unwrap().tile_count, 1);
+    // 8の下にある2とはつながる
+    assert!(board.is_connected(0, 1, 0));
+    assert!(board.is_connected(1, 1, 2));
+    assert_eq!(board.to_chars(), vec![vec!['4', '8'], vec!['0', '2']]);
+}
+
+#[test]
+fn components_cover_whole_board() {
+    // 左上にループ(4枚)，右の列から下の行へ曲がる木(4枚)，左下は空きマス
+    let board = vec![
+        vec!['c', '9', '8'],
+        vec!['6', '3', 'a'],
+        vec!['0', '4', '3'],
+    ];
+    let (board, _) = Board::from_chars(&board);
+    let analysis = analyze_components(&board);
+    let summary: Vec<_> = analysis.components.iter().map(|c| (c.tile_count, c.edge_count, c.has_cycle)).collect();
+    assert_eq!(summary, vec![(4, 4, true), (4, 3, false)]);
+    assert_eq!(analysis.component_index(2, 0), None);
+    assert_eq!(analysis.largest_tree(), Some(1));
+    assert_eq!(analysis.max_tree_size(), 4);
+}
+
+#[test]
+fn game_state_tracks_largest_tree_anywhere() {
+    // 右下の空きマスの上のタイル(単独)を動かしても，左上の大きい木(5枚)が最大の木のまま
+    // 真ん中の列の下2枚は2枚だけの木
+    let input = Input {
+        n: 3,
+        t: 54,
+        board: ["c51", "a88", "220"].iter().map(|line| line.chars().collect()).collect(),
+    };
+    let mut state = GameState::new(&input);
+    assert_eq!(state.empty, (2, 2));
+    state.advance(2);
+    assert_eq!(state.tree_size, 1);
+    assert_eq!(state.max_tree_size, 5);
+}
+
+#[test]
+fn zobrist_hash_is_updated_incrementally() {
+    let input = sample_input();
+    let mut state = GameState::new(&input);
+    let initial = state.hash;
+    for c in "RRRDLUULDDDDLUUUR".chars() {
+        state.advance(char_to_action(c).unwrap());
+        assert_eq!(state.hash, hash_board(&state.board, state.empty));
+    }
+    // 行って戻れば元の盤面と同じハッシュになる
+    let before = state.hash;
+    state.advance(char_to_action('D').unwrap());
+    assert_ne!(state.hash, before);
+    state.advance(char_to_action('U').unwrap());
+    assert_eq!(state.hash, before);
+    let mut seen = SeenStates::new(1);
+    assert!(seen.visit(state.hash, 19));
+    assert!(!seen.visit(state.hash, 20));
+    assert!(seen.visit(state.hash, 3));
+    // 上限を超えた盤面は覚えない
+    assert!(seen.visit(initial, 0));
+    assert!(seen.visit(initial, 0));
+    assert_eq!(seen.len(), 1);
+}
+
+#[test]
+fn cycle_basis_lists_each_independent_cycle() {
+    // 2x3のはしご状の盤面には独立な閉路が2つある
+    // c d 9
+    // 6 7 3
+    let (board, _) = Board::from_chars(&[vec!['c', 'd', '9'], vec!['6', '7', '3'], vec!['0', '0', '0']]);
+    let cycles = find_cycles(&board);
+    assert_eq!(cycles.len(), 2);
+    for cycle in &cycles {
+        // 基底の選び方によっては外周(長さ6)が選ばれることもある
+        assert!(cycle.len() == 4 || cycle.len() == 6);
+        assert_eq!(cycle.cells.len(), cycle.len());
+        for &(a, b) in &cycle.edges {
+            assert_eq!((a.0 as i32 - b.0 as i32).abs() + (a.1 as i32 - b.1 as i32).abs(), 1);
+        }
+    }
+    assert!(find_cycles(&Board::from_chars(&[vec!['c', '1'], vec!['2', '0']]).0).is_empty());
+    assert_eq!(render_svg(&board).matches("class=\"cycle\"").count(), 2);
+}
+
+#[test]
+fn weighted_evaluator_reads_weights_from_string() {
+    let weights = Weights::parse("tree_size=2, turn=-0.5").unwrap();
+    assert_eq!(weights.tree_size, 2.0);
+    assert_eq!(weights.turn, -0.5);
+    assert_eq!(weights.cycle_count, Weights::default().cycle_count);
+    assert!(Weights::parse("tree_size").is_err());
+    assert!(Weights::parse("depth=1").is_err());
+
+    let input = sample_input();
+    let mut state = GameState::new(&input);
+    let action = state.get_legal_actions()[0];
+    state.advance(action);
+    let evaluator = WeightedEvaluator { weights };
+    let features = Features::from_state(&state);
+    assert_eq!(features.turn, 1.0);
+    assert_eq!(evaluator.evaluate(&state), evaluator.score(&features));
+}
+
+#[test]
+fn longer_cycle_scores_worse_than_shorter_one() {
+    // 左上に2x2の閉路(長さ4)だけがある盤面と，2x3の閉路(長さ6)だけがある盤面
+    // c 9 0    c 5 9
+    // 6 3 0    6 5 3
+    // 0 0 0    0 0 0
+    let short = Input { n: 3, t: 0, board: vec!["c90".chars().collect(), "630".chars().collect(), "000".chars().collect()] };
+    let long = Input { n: 3, t: 0, board: vec!["c59".chars().collect(), "653".chars().collect(), "000".chars().collect()] };
+    let (short, long) = (GameState::new(&short), GameState::new(&long));
+    let (fs, fl) = (Features::from_state(&short), Features::from_state(&long));
+    assert_eq!((fs.cycle_count, fs.cycle_length), (1.0, 4.0));
+    assert_eq!((fl.cycle_count, fl.cycle_length), (1.0, 6.0));
+    let evaluator = WeightedEvaluator { weights: Weights::default() };
+    assert!(evaluator.evaluate(&long) < evaluator.evaluate(&short));
+    assert_eq!(Weights::parse("cycle_length=-1").unwrap().cycle_length, -1.0);
+}
+
+#[test]
+fn dangling_counts_border_and_mismatched_half_edges() {
+    // 1 1
+    // 2 0
+    // 左上の1は盤面の外を，右上の1と左下の2は向かい合う道がないタイルを向いている
+    let (board, _) = Board::from_chars(&[vec!['1', '1'], vec!['2', '0']]);
+    let dangling = count_dangling(&board);
+    assert_eq!(dangling.border, 1);
+    assert_eq!(dangling.mismatched, 2);
+    assert_eq!(dangling.total(), 3);
+    // 全域木ならつながらない道はない
+    let input = gen(3, Some(6));
+    let mut state = GameState::new(&input);
+    assert!(state.dangling.total() > 0);
+    let (solved, _) = Board::from_chars(&[vec!['4', '5', '1'], vec!['0', '0', '0'], vec!['0', '0', '0']]);
+    assert_eq!(count_dangling(&solved).total(), 0);
+    let action = state.get_legal_actions()[0];
+    state.advance(action);
+    assert_eq!(state.dangling, count_dangling(&state.board));
+}
+
+#[test]
+fn arrangement_annealing_finds_spanning_tree() {
+    let input = gen(0, Some(6));
+    let (board, _) = Board::from_chars(&input.board);
+    let mut annealing = ArrangementAnnealing {
+        empty: (5, 5),
+        start_temperature: 0.5,
+        end_temperature: 0.01,
+        deadline: Instant::now() + Duration::from_secs(10),
+        budget: Duration::from_secs(10),
+        rng: new_rng(0),
+    };
+    let arrangement = annealing.search(&board);
+    assert!(arrangement.is_spanning_tree());
+    assert_eq!(arrangement_cost(&arrangement.board), 0);
+    assert_eq!(arrangement.board.get(5, 5), 0);
+    assert_eq!(compute_official_tree_size(&arrangement.board), 35);
+    // タイルを並べ替えただけで，種類ごとの枚数は変わらない
+    let mut before = board.tiles[..36].to_vec();
+    let mut after = arrangement.board.tiles[..36].to_vec();
+    before.sort_unstable();
+    after.sort_unstable();
+    assert_eq!(before, after);
+
+    // 締め切りが遠くても1回のsearchはbudgetで切り上げ，全域木でなくてもそれまでのベストを返す
+    let input = gen(1, Some(10));
+    let (board, _) = Board::from_chars(&input.board);
+    annealing.empty = (9, 9);
+    annealing.budget = Duration::from_millis(1);
+    let start = Instant::now();
+    let arrangement = annealing.search(&board);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(arrangement.cost, arrangement_cost(&arrangement.board));
+}
+
+#[test]
+fn route_reaches_target_arrangement() {
+    let mut solved = 0;
+    for seed in 0..10 {
+        let input = gen(seed, Some(6 + seed as usize % 5));
+        let n = input.n;
+        let (board, empty) = Board::from_chars(&input.board);
+        // 入力から適当に動かした盤面を目標配置にする
+        let mut target = board;
+        let mut target_empty = empty;
+        let mut operation_list = vec![];
+        for k in 0..1000 {
+            let actions = get_legal_actions(&operation_list, target_empty, n);
+            let action = actions[k % actions.len()];
+            target_empty = target.slide(target_empty, action);
+            operation_list.push(ACTION_CHARS[action]);
+        }
+        // 偶奇を合わせた対応に従えば必ずたどり着ける
+        let assignment = plan_assignment(&board, empty, &target, target_empty).unwrap();
+        let operations = route_with_assignment(&board, empty, &target, target_empty, &assignment).unwrap();
+        let result = replay(&input, &operations, input.t).unwrap();
+        assert_eq!(result.board, target);
+        assert_eq!(result.empty, target_empty);
+
+        // 同じ種類のタイルをその場で選ぶと，最後の2x3の偶奇が合わないことがある
+        let operations = match route(&board, empty, &target, target_empty) {
+            Ok(operations) => operations,
+            Err(e) => {
+                assert_eq!(e, RouteError::Unsolvable);
+                continue;
+            }
+        };
+        let result = replay(&input, &operations, input.t).unwrap();
+        assert_eq!(result.empty, target_empty);
+        assert_eq!(result.board, target);
+        solved += 1;
+    }
+    assert!(solved > 0);
+}
+
+#[test]
+fn parity_check_repairs_with_identical_tiles() {
+    // 種類がすべて違うタイルは，2枚を入れ替えた配置にたどり着けない
+    // 1 2    2 1
+    // 4 0    4 0
+    let (board, empty) = Board::from_chars(&[vec!['1', '2'], vec!['4', '0']]);
+    let (swapped, _) = Board::from_chars(&[vec!['2', '1'], vec!['4', '0']]);
+    assert_eq!(check_reachability(&board, empty, &swapped, empty), Err(ParityError::NoIdenticalTiles));
+    let (rotated, rotated_empty) = Board::from_chars(&[vec!['1', '2'], vec!['0', '4']]);
+    assert!(check_reachability(&board, empty, &rotated, rotated_empty).is_ok());
+    let (other, _) = Board::from_chars(&[vec!['1', '2'], vec!['8', '0']]);
+    assert_eq!(check_reachability(&board, empty, &other, empty), Err(ParityError::TileCountMismatch));
+
+    // 同じ種類のタイルがあれば，行き先を入れ替えて偶奇を直せる
+    // 1 1 2    1 2 1
+    // 4 4 8    4 4 8
+    // 8 2 0    8 2 0
+    let (board, empty) = Board::from_chars(&[vec!['1', '1', '2'], vec!['4', '4', '8'], vec!['8', '2', '0']]);
+    let (target, target_empty) = Board::from_chars(&[vec!['1', '2', '1'], vec!['4', '4', '8'], vec!['8', '2', '0']]);
+    let natural = Assignment::natural(&board, &target).unwrap();
+    assert!(!natural.is_reachable(empty, target_empty));
+    let repaired = check_reachability(&board, empty, &target, target_empty).unwrap();
+    assert!(repaired.is_reachable(empty, target_empty));
+    for (q, &p) in repaired.source_of.iter().enumerate() {
+        assert_eq!(board.tiles[p], target.tiles[q]);
+    }
+}
+
+#[test]
+fn hungarian_finds_min_cost_assignment() {
+    let cost = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+    let column_of = hungarian(&cost);
+    let total: i64 = column_of.iter().enumerate().map(|(i, &j)| cost[i][j]).sum();
+    assert_eq!(total, 5);
+    let mut columns = column_of.clone();
+    columns.sort_unstable();
+    assert_eq!(columns, vec![0, 1, 2]);
+
+    // 目標配置への対応は，種類ごとの順番の対応より移動距離が長くならない
+    let input = gen(1, None);
+    let (board, empty) = Board::from_chars(&input.board);
+    let (target, _) = Board::from_chars(&gen(1, None).board.iter().rev().cloned().collect::<Vec<_>>());
+    let target_empty = (input.n - 1 - empty.0, empty.1);
+    let natural = Assignment::natural(&board, &target).unwrap();
+    let min_cost = Assignment::min_cost(&board, &target).unwrap();
+    assert!(min_cost.total_distance() <= natural.total_distance());
+    assert!(plan_assignment(&board, empty, &target, target_empty).unwrap().is_reachable(empty, target_empty));
+}
+
+#[test]
+fn sequence_annealing_returns_legal_operations() {
+    let input = sample_input();
+    let mut solver = SequenceAnnealing {
+        max_segment_len: 4,
+        start_temperature: 5000.0,
+        end_temperature: 100.0,
+        deadline: Instant::now() + Duration::from_millis(300),
+        rng: new_rng(0),
+    };
+    let operations = solver.solve(&input);
+    let result = replay(&input, &operations, input.t).unwrap();
+    // 前の手を打ち消す手は含まない
+    for pair in operations.windows(2) {
+        let action = char_to_action(pair[1]).unwrap();
+        assert_ne!(pair[0], ACTION_CHARS[reverse_action(action)]);
+    }
+    let (board, _) = Board::from_chars(&input.board);
+    assert!(compute_official_tree_size(&result.board) >= compute_official_tree_size(&board));
+}
+
+// boardから，movableなマスの中だけでk手ランダムに動かした盤面
+fn scramble(board: &Board, empty: (usize, usize), movable: &[bool], k: usize, seed: u64) -> (Board, (usize, usize)) {
+    let mut rng = new_rng(seed);
+    let n = board.n;
+    let (mut board, mut empty) = (*board, empty);
+    for _ in 0..k {
+        let actions: Vec<usize> = (0..4).filter(|&a| board.neighbor(empty.0, empty.1, a).is_some_and(|(x, y)| movable[x * n + y])).collect();
+        empty = board.slide(empty, actions[rng.gen_range(0, actions.len())]);
+    }
+    (board, empty)
+}
+
+#[test]
+fn ida_star_solves_small_boards_and_sub_regions() {
+    let ida = IdaStar { node_limit: 10_000_000, deadline: Instant::now() + Duration::from_secs(30) };
+    // 3x3の盤面全体
+    let (target, target_empty) = Board::from_chars(&[vec!['1', '2', '3'], vec!['4', '5', '6'], vec!['7', '8', '0']]);
+    let movable = vec![true; 9];
+    for seed in 0..5 {
+        let (board, empty) = scramble(&target, target_empty, &movable, 30, seed);
+        let operations = ida.solve(&board, empty, &target, &movable).unwrap();
+        let input = Input { n: 3, t: 100, board: board.to_chars() };
+        let result = replay(&input, &operations, input.t).unwrap();
+        assert_eq!(result.board, target);
+        assert!(operations.len() <= 30);
+    }
+    // 6x6の右下2x3だけ
+    let input = gen(0, Some(6));
+    let (mut large, mut large_empty) = Board::from_chars(&input.board);
+    while large_empty != (5, 5) {
+        large_empty = large.slide(large_empty, if large_empty.0 < 5 { 0 } else { 1 });
+    }
+    let movable: Vec<bool> = (0..36).map(|p| p / 6 >= 4 && p % 6 >= 3).collect();
+    let (board, empty) = scramble(&large, large_empty, &movable, 40, 1);
+    let operations = ida.solve(&board, empty, &large, &movable).unwrap();
+    let mut replayed = board;
+    let mut replayed_empty = empty;
+    for &c in &operations {
+        replayed_empty = replayed.slide(replayed_empty, char_to_action(c).unwrap());
+    }
+    assert_eq!((replayed, replayed_empty), (large, large_empty));
+    // 2枚を入れ替えた配置にはたどり着けない
+    let (swapped, _) = Board::from_chars(&[vec!['2', '1', '3'], vec!['4', '5', '6'], vec!['7', '8', '0']]);
+    assert_eq!(ida.solve(&target, target_empty, &swapped, &[true; 9]), Err(IdaError::Unsolvable));
+    // 2x2の閉路に1マスぶら下がった領域は関節点があり，同じ種類のタイルがあっても偶奇を直せない
+    let (ring, ring_empty) = Board::from_chars(&[vec!['1', '2', '0'], vec!['3', '1', '4'], vec!['4', '4', '4']]);
+    let (reordered, _) = Board::from_chars(&[vec!['1', '1', '0'], vec!['3', '2', '4'], vec!['4', '4', '4']]);
+    let region: Vec<bool> = (0..9).map(|p| p < 5).collect();
+    let limited = IdaStar { node_limit: 1_000_000, deadline: Instant::now() + Duration::from_secs(30) };
+    assert_eq!(limited.solve(&ring, ring_empty, &reordered, &region), Err(IdaError::Unsolvable));
+    // ノード数の上限で打ち切る
+    let (board, empty) = scramble(&target, target_empty, &[true; 9], 200, 7);
+    let limited = IdaStar { node_limit: 10, deadline: Instant::now() + Duration::from_secs(30) };
+    assert_eq!(limited.solve(&board, empty, &target, &[true; 9]), Err(IdaError::NodeLimit));
+}
+
+#[test]
+fn pattern_database_bounds_the_last_rows() {
+    // 6x6の下2行
+    let input = gen(4, Some(6));
+    let (mut target, mut target_empty) = Board::from_chars(&input.board);
+    while target_empty != (5, 5) {
+        target_empty = target.slide(target_empty, if target_empty.0 < 5 { 0 } else { 1 });
+    }
+    let movable: Vec<bool> = (0..36).map(|p| p / 6 >= 4).collect();
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let database = PatternDatabase::build(&target, target_empty, &movable, 400_000, deadline).unwrap();
+    assert!(database.group_count() >= 2);
+    assert_eq!(database.lower_bound(&target), 0);
+    let ida = IdaStar { node_limit: 10_000_000, deadline };
+    let mut tighter = 0;
+    for seed in 0..6 {
+        let (board, empty) = scramble(&target, target_empty, &movable, 40, seed);
+        let operations = ida.solve_with_database(&board, empty, &database).unwrap();
+        // pattern databaseを使った下界は，マンハッタン距離とlinear conflictの下界以上で，最短手数以下
+        let manhattan = lower_bound(&board, &target, &movable, None);
+        let bound = lower_bound(&board, &target, &movable, Some(&database));
+        assert!(manhattan <= bound && bound <= operations.len() as i64);
+        assert!(database.lower_bound(&board) <= operations.len() as i64);
+        if bound > manhattan {
+            tighter += 1;
+        }
+        // 手数はpattern databaseを使わないときと同じ
+        assert_eq!(operations.len(), ida.solve(&board, empty, &target, &movable).unwrap().len());
+        let mut replayed = board;
+        let mut replayed_empty = empty;
+        for &c in &operations {
+            replayed_empty = replayed.slide(replayed_empty, char_to_action(c).unwrap());
+        }
+        assert_eq!((replayed, replayed_empty), (target, target_empty));
+    }
+    assert!(tighter > 0);
+    assert_eq!(PatternDatabase::build(&target, target_empty, &[false; 36], 400_000, deadline).err(), Some(PdbError::EmptyOutsideRegion));
+    assert_eq!(PatternDatabase::build(&target, target_empty, &movable, 400_000, Instant::now()).err(), Some(PdbError::TimeLimit));
+}
+
+// 盤面と空きマスの組を1手ずつ幅優先で広げ，targetまでの最短手数を求める
+fn bfs_distance(board: &Board, empty: (usize, usize), target: &Board, target_empty: (usize, usize)) -> usize {
+    let mut seen = std::collections::HashSet::new();
+    seen.insert((*board, empty));
+    let mut frontier = vec![(*board, empty)];
+    for depth in 0.. {
+        if frontier.contains(&(*target, target_empty)) {
+            return depth;
+        }
+        let mut next_frontier = vec![];
+        for &(state, state_empty) in &frontier {
+            for action in (0..4).filter(|&action| state.neighbor(state_empty.0, state_empty.1, action).is_some()) {
+                let mut next = state;
+                let next_empty = next.slide(state_empty, action);
+                if seen.insert((next, next_empty)) {
+                    next_frontier.push((next, next_empty));
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+    unreachable!()
+}
+
+#[test]
+fn bidirectional_search_matches_plain_bfs() {
+    // 両方向探索の手数は，盤面を1手ずつ幅優先で広げたときの最短手数と一致する
+    let search = BidirectionalSearch { node_limit: 1_000_000, deadline: Instant::now() + Duration::from_secs(30) };
+    for seed in 0..20 {
+        let input = gen(seed, Some(3));
+        let (board, empty) = Board::from_chars(&input.board);
+        let (target, target_empty) = scramble(&board, empty, &[true; 9], 40, seed);
+        let operations = search.solve(&board, empty, &target, target_empty).unwrap();
+        assert_eq!(operations.len(), bfs_distance(&board, empty, &target, target_empty), "seed {}", seed);
+        let unlimited = Input { t: usize::MAX, ..input.clone() };
+        let result = replay(&unlimited, &operations, operations.len()).unwrap();
+        assert_eq!((result.board, result.empty), (target, target_empty));
+    }
+}
+
+#[test]
+fn bidirectional_search_joins_forward_and_backward() {
+    let input = gen(2, Some(8));
+    let (board, empty) = Board::from_chars(&input.board);
+    let movable = [true; 64];
+    let (target, target_empty) = scramble(&board, empty, &movable, 14, 3);
+    let search = BidirectionalSearch { node_limit: 1_000_000, deadline: Instant::now() + Duration::from_secs(30) };
+    let operations = search.solve(&board, empty, &target, target_empty).unwrap();
+    assert!(operations.len() <= 14);
+    let result = replay(&input, &operations, input.t).unwrap();
+    assert_eq!((result.board, result.empty), (target, target_empty));
+    assert_eq!(search.solve(&board, empty, &board, empty), Ok(vec![]));
+
+    // 行って戻って行く遠回りを縮める
+    let back: Vec<char> = operations.iter().rev().map(|&c| ACTION_CHARS[reverse_action(char_to_action(c).unwrap())]).collect();
+    let detour: Vec<char> = operations.iter().chain(&back).chain(&operations).copied().collect();
+    let shortened = search.shorten(&board, empty, &detour, 8);
+    assert!(shortened.len() < detour.len());
+    let result = replay(&input, &shortened, input.t).unwrap();
+    assert_eq!((result.board, result.empty), (target, target_empty));
+
+    let (far, far_empty) = scramble(&board, empty, &movable, 500, 4);
+    let limited = BidirectionalSearch { node_limit: 1000, deadline: Instant::now() + Duration::from_secs(30) };
+    assert_eq!(limited.solve(&board, empty, &far, far_empty), Err(BidirectionalError::NodeLimit));
+
+    // 2枚を入れ替えた配置は置換の偶奇が合わず，探索し尽くしてもたどり着けない
+    let (small, small_empty) = Board::from_chars(&[vec!['1', '2'], vec!['4', '0']]);
+    let (swapped, swapped_empty) = Board::from_chars(&[vec!['2', '1'], vec!['4', '0']]);
+    assert_eq!(search.solve(&small, small_empty, &swapped, swapped_empty), Err(BidirectionalError::Unreachable));
+}
+
+#[test]
+fn row_by_row_search_builds_trees_row_by_row() {
+    // どの行(列)でも制約を満たす種類の選び方が見つかる入力．見つからないと貪欲に選ぶので，木を伸ばせなくなることがある
+    let input = gen(12, Some(8));
+    let n = input.n;
+    // 1行(1列)揃えるごとに，前に固定したマスのタイルが動いておらず，固定したマスの種類で木を伸ばせることを確かめる
+    fn check_prefix(builder: &Builder, before: &[(usize, u8)]) -> Vec<(usize, u8)> {
+        let n = builder.state.n;
+        for &(p, tile) in before {
+            assert!(builder.router.locked[p]);
+            assert_eq!(builder.state.board.tiles[p], tile);
+        }
+        assert!(builder.is_extendable());
+        let locked: Vec<usize> = (0..n * n).filter(|&p| builder.router.locked[p]).collect();
+        assert!(locked.len() > before.len());
+        // 固定したマスには選んだ種類のタイルがある
+        assert!(locked.iter().all(|&p| builder.state.board.tiles[p] == builder.router.target.tiles[p]));
+        locked.into_iter().map(|p| (p, builder.state.board.tiles[p])).collect()
+    }
+    let mut rng = new_rng(0);
+    let mut builder = Builder::new(&input);
+    let mut locked = vec![];
+    for r in 0..n - 2 {
+        builder.build_row(r, &mut rng).unwrap();
+        locked = check_prefix(&builder, &locked);
+        assert!((0..n).all(|c| builder.router.locked[r * n + c]));
+    }
+    for c in 0..n - 3 {
+        builder.build_column(c, &mut rng).unwrap();
+        locked = check_prefix(&builder, &locked);
+        assert!(builder.router.locked[(n - 2) * n + c] && builder.router.locked[(n - 1) * n + c]);
+    }
+    builder.finish();
+    assert!(locked.iter().all(|&(p, tile)| builder.state.board.tiles[p] == tile));
+    let state = builder.state;
+    // constructは同じ手順で作る
+    assert_eq!(construct(&input, &mut new_rng(0), Instant::now() + Duration::from_secs(10)).unwrap().operation_list, state.operation_list);
+    let unlimited = Input { t: usize::MAX, ..input.clone() };
+    let result = replay(&unlimited, &state.operation_list.to_chars(), state.operation_list.len()).unwrap();
+    assert_eq!((result.board, result.empty), (state.board, state.empty));
+    assert_eq!(state.turn, state.operation_list.len());
+
+    let mut solver = RowByRowSearch { deadline: Instant::now() + Duration::from_millis(200), rng: new_rng(1) };
+    let operations = solver.solve(&input);
+    let result = replay(&input, &operations, input.t).unwrap();
+    let (board, _) = Board::from_chars(&input.board);
+    assert!(compute_official_tree_size(&result.board) > compute_official_tree_size(&board));
+}
+
+#[test]
+fn row_by_row_search_stops_mid_construction_at_deadline() {
+    // 1回の構築が終わるのを待たず，行の間で締め切りを確かめて途中までの手順から選ぶ
+    let input = gen(118, Some(10));
+    let start = Instant::now();
+    let mut solver = RowByRowSearch { deadline: start + Duration::from_millis(1), rng: new_rng(0) };
+    let operations = solver.solve(&input);
+    assert!(start.elapsed() < Duration::from_millis(200));
+    assert!(operations.len() <= input.t);
+    // 締め切りまでに1行も揃えられなくても，入力より悪くはしない
+    let (board, _) = Board::from_chars(&input.board);
+    let result = replay(&input, &operations, input.t).unwrap();
+    assert!(compute_official_tree_size(&result.board) >= compute_official_tree_size(&board));
+}
+
+#[test]
+fn puzzle_search_falls_back_to_best_prefix() {
+    // Tが短すぎて目標配置まで動かせないときも，空ではなくT手以内で木を大きくした手順を返す
+    let mut input = gen(2, Some(6));
+    input.t = 20;
+    let mut solver = PuzzleSearch {
+        annealing: ArrangementAnnealing {
+            empty: (5, 5),
+            start_temperature: 0.5,
+            end_temperature: 0.01,
+            deadline: Instant::now() + Duration::from_millis(500),
+            budget: Duration::from_millis(500),
+            rng: new_rng(0),
+        },
+        shortcut_window: 0,
+        last_rows_node_limit: 0,
+    };
+    let operations = solver.solve(&input);
+    assert!(!operations.is_empty());
+    let result = replay(&input, &operations, input.t).unwrap();
+    let (board, _) = Board::from_chars(&input.board);
+    assert!(compute_official_tree_size(&result.board) > compute_official_tree_size(&board));
+}
+
+#[test]
+fn puzzle_search_routes_the_best_arrangement_when_annealing_runs_out_of_time() {
+    // 焼きなましで全域木が見つからないほど短い時間でも，空ではなくT手以内で木を大きくした手順を返す
+    let input = gen(3, Some(10));
+    let mut solver = PuzzleSearch {
+        annealing: ArrangementAnnealing {
+            empty: (9, 9),
+            start_temperature: 0.5,
+            end_temperature: 0.01,
+            deadline: Instant::now() + Duration::from_millis(1),
+            budget: Duration::from_millis(1),
+            rng: new_rng(0),
+        },
+        shortcut_window: 16,
+        last_rows_node_limit: 2_000_000,
+    };
+    let operations = solver.solve(&input);
+    assert!(!operations.is_empty());
+    let result = replay(&input, &operations, input.t).unwrap();
+    let (board, _) = Board::from_chars(&input.board);
+    assert!(compute_official_tree_size(&result.board) > compute_official_tree_size(&board));
+}
+
+#[test]
+fn main_solver_replays_with_seed_and_iterations() {
+    // シードと回数を指定すれば，時間によらず同じ出力になる
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let input = gen(5, Some(8));
+    let run = || {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ahc011-a"))
+            .args(["--seed", "42", "--iterations", "3"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.to_string().as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        output.stdout
+    };
+    let first = run();
+    assert_eq!(first, run());
+    let operations = parse_output(&String::from_utf8(first).unwrap());
+    assert!(!operations.is_empty());
+    assert!(replay(&input, &operations, input.t).is_ok());
+}
+
+#[test]
+fn malformed_weights_exit_with_usage() {
+    // 重みを書き間違えたらpanicせず，使い方を出して終了コード2で終わる
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let input = gen(5, Some(6));
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ahc011-a"))
+        .env("AHC011_WEIGHTS", "depth=1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.to_string().as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("usage:") && !stderr.contains("panicked"));
+}
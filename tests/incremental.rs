@@ -0,0 +1,41 @@
+use ahc011::board::GameState;
+use ahc011::evaluation::{analyze_components, count_dangling, find_cycles, Component};
+use ahc011::gen::gen;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_pcg::Pcg64Mcg;
+
+// 連結成分の並び順は更新の仕方で変わるので，並べ替えてから比べる
+fn sorted(mut components: Vec<Component>) -> Vec<Component> {
+    components.sort_by_key(|c| (c.min_x, c.min_y, c.max_x, c.max_y, c.tile_count, c.edge_count));
+    components
+}
+
+#[test]
+fn incremental_update_matches_full_recomputation() {
+    for seed in 0..20 {
+        let input = gen(seed, None);
+        let n = input.n;
+        let mut rng = Pcg64Mcg::seed_from_u64(seed);
+        let mut state = GameState::new(&input);
+        for _ in 0..500 {
+            let action = *state.get_legal_actions().choose(&mut rng).unwrap();
+            state.advance(action);
+
+            let analysis = analyze_components(&state.board);
+            assert_eq!(sorted(state.components.components()), sorted(analysis.components.clone()), "seed {} turn {}", seed, state.turn);
+            assert_eq!(state.components.max_tree_size(), analysis.max_tree_size());
+            assert_eq!(state.dangling, count_dangling(&state.board));
+            assert_eq!(state.components.cycle_length(), find_cycles(&state.board).iter().map(|c| c.len()).sum::<usize>());
+            // 各マスの属する連結成分も一致する
+            for a in 0..n * n {
+                for b in 0..n * n {
+                    let (pa, pb) = ((a / n, a % n), (b / n, b % n));
+                    let same = analysis.component_of[a] != usize::MAX && analysis.component_of[a] == analysis.component_of[b];
+                    assert_eq!(state.components.same(pa, pb), same);
+                }
+                assert_eq!(state.components.component_at(a / n, a % n), analysis.component_at(a / n, a % n).copied());
+            }
+        }
+    }
+}